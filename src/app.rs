@@ -1,6 +1,31 @@
-use crate::{app_config::to_window_level, components::*, constant::RATIO, times::use_current_time};
+use crate::{
+    app_config::{to_window_level, AppConfig, ClockMode, LockMode},
+    click_sound::DigitGroup,
+    components::*,
+    constant::{MAX_SIZE, MIN_SIZE, RATIO},
+    control::{use_control_socket, use_http_server},
+    countdown::MAX_DAYS,
+    digit_layout::{digit_count, layout_scale},
+    event_hooks::use_event_hooks,
+    hooks::{
+        use_audio, use_calendar, use_chime, use_click_sound_provider, use_countdown, use_flash,
+        use_idle_fade, use_monitors, use_snap, use_window_animation, use_window_placement,
+        UseCountdown,
+    },
+    instance::use_instance_server,
+    keymap::{effective_keymap, find_action, KeyAction},
+    snap::snap_position,
+    times::{use_current_time, use_current_time_provider},
+};
 use freya::prelude::*;
 
+// 方向键每次移动窗口的距离，单位是物理像素
+const NUDGE_STEP: f32 = 5.0;
+// +/- 每次缩放的比例
+const RESIZE_STEP: f64 = 1.1;
+// 日历提醒和倒计时结束闪烁时变暗的程度
+const FLASH_OPACITY: f32 = 0.3;
+
 #[allow(non_snake_case)]
 #[component]
 pub fn App() -> Element {
@@ -20,24 +45,74 @@ pub fn MyApp() -> Element {
     let mut app_conf = app_config_context.app_conf;
     let card_color = app_conf().card_color;
     let platform = use_platform();
-    let scale_factor = use_scale_factor().0;
 
-    let radius = app_conf().size as f32 / scale_factor() * 0.04285 * 0.33333;
+    let radius = app_conf().size as f32 * 0.04285 * 0.33333;
 
     let mut handle_lock = move || {
-        app_conf.write().lock = !app_conf().lock;
+        app_conf.write().lock = app_conf().lock.toggle_lock();
+    };
+
+    let mut handle_ghost = move || {
+        app_conf.write().lock = app_conf().lock.toggle_ghost();
     };
 
+    // 幽灵模式下鼠标事件穿透到下面的窗口，只能通过快捷键或者命令行退出
+    let is_ghost = app_conf().lock == LockMode::Ghost;
+    use_effect(use_reactive(&is_ghost, move |is_ghost| {
+        platform.with_window(move |window| {
+            let _ = window.set_cursor_hittest(!is_ghost);
+        });
+    }));
+
+    let monitors = use_monitors();
+    let mut snap = use_snap(monitors);
+    let placement = use_window_placement(monitors, app_conf().position_preset);
+
     let handle_window_moved = move |e: WindowMovedEvent| {
+        let window_size = platform.info().window_size;
+        let window = Area::new(Point2D::new(e.get_x() as f32, e.get_y() as f32), window_size);
+        let is_placed = placement.is_placed(window.origin);
+
+        let snap_distance = app_conf().snap_distance;
+        if snap_distance > 0.0 && !snap.is_bypassed() && !is_placed {
+            let position = snap_position(window, &snap.lines(), snap_distance);
+            // 移动到吸附的位置之后会再次收到移动事件，到时再记录位置
+            if position != window.origin {
+                platform.set_window_size_and_position(window_size, position);
+                return;
+            }
+        }
+
         app_conf.write().x = e.get_x();
         app_conf.write().y = e.get_y();
+        // 用户拖走了窗口，不再使用预设位置
+        if !is_placed && app_conf().position_preset.is_some() {
+            app_conf.write().position_preset = None;
+        }
+        snap.publish_window(window);
     };
 
     let handle_size_change = move |(new_size, _): (Size2D, Size2D)| {
-        app_conf.write().size = new_size.width as f64;
+        let scale_factor = platform.info().window_scale_factor;
+        app_conf.write().size = (new_size.width / scale_factor) as f64;
     };
 
     let mut opacity = use_signal(|| "0");
+    let mut idle_fade = use_idle_fade(app_conf);
+
+    use_control_socket(app_config_context);
+    use_instance_server(app_config_context);
+    let event_hooks = use_event_hooks(app_conf);
+    let flash = use_flash(idle_fade);
+    // 翻页声音、报时和日历提醒共用一个音频输出
+    let audio = use_audio(app_conf);
+    // 主区域和工具栏里的日历倒计时都有翻页卡片
+    use_click_sound_provider(app_conf, audio.clone());
+    let calendar = use_calendar(app_conf, event_hooks, flash, audio.clone());
+    let mut countdown = use_countdown(app_conf, event_hooks, flash, idle_fade);
+    // 报时不依赖数字的显示，放在最外层，和下面的数字共用一个时钟
+    let current_time = use_current_time_provider();
+    use_chime(app_conf, audio, current_time);
 
     let window_level = to_window_level(app_conf().window_level);
 
@@ -46,21 +121,179 @@ pub fn MyApp() -> Element {
         platform.set_window_level(to_window_level(app_conf().window_level));
     };
 
+    let mut window_animation = use_window_animation();
+
+    let mut handle_profile = move || {
+        let Some(next_profile) = app_conf().next_profile() else {
+            return;
+        };
+        app_conf.write().switch_profile(&next_profile);
+
+        let app_conf = app_conf();
+        platform.set_window_level(to_window_level(app_conf.window_level));
+        window_animation.animate_to(
+            app_conf.window_size(platform.info().window_scale_factor),
+            Point2D::new(app_conf.x as f32, app_conf.y as f32),
+            300,
+        );
+    };
+
+    let mut show_settings = use_signal(|| false);
+
+    let mut handle_reset_position = move || {
+        let AppConfig { x, y, .. } = AppConfig::default();
+        app_conf.write().position_preset = None;
+        window_animation.animate_to(
+            app_conf().window_size(platform.info().window_scale_factor),
+            Point2D::new(x as f32, y as f32),
+            300,
+        );
+    };
+
+    let mut handle_position_preset = move || {
+        let preset = placement.next_preset(app_conf().position_preset);
+        app_conf.write().position_preset = Some(preset);
+    };
+
+    let mut menu_position = use_signal(|| None as Option<Point2D>);
+
+    let mut show_help = use_signal(|| false);
+
+    let handle_nudge = move |delta_x: f32, delta_y: f32| {
+        let PlatformInformation {
+            window_size,
+            window_position,
+            ..
+        } = platform.info();
+        platform.set_window_size_and_position(
+            window_size,
+            Point2D::new(window_position.x + delta_x, window_position.y + delta_y),
+        );
+    };
+
+    let mut handle_resize = move |scale: f64| {
+        let size = (app_conf().size * scale).clamp(MIN_SIZE, MAX_SIZE).round();
+        app_conf.write().size = size;
+        let PlatformInformation {
+            window_position,
+            window_scale_factor,
+            ..
+        } = platform.info();
+        platform.set_window_size_and_position(
+            app_conf().window_size(window_scale_factor),
+            window_position,
+        );
+    };
+
+    let onkeyup = move |e: KeyboardEvent| {
+        if e.key == Key::Shift {
+            snap.set_bypass(false);
+        }
+    };
+
+    let onkeydown = move |e: KeyboardEvent| {
+        idle_fade.wake();
+        // 按住 Shift 拖动时不吸附
+        if e.key == Key::Shift {
+            snap.set_bypass(true);
+        }
+        if menu_position().is_some() {
+            return;
+        }
+        if show_help() {
+            if e.key == Key::Escape {
+                show_help.set(false);
+            }
+            return;
+        }
+        // 设置面板中的输入框需要这些按键
+        if show_settings() {
+            if e.key == Key::Escape {
+                show_settings.set(false);
+            }
+            return;
+        }
+
+        let keymap = effective_keymap(&app_conf().keymap);
+        let Some(action) = find_action(&keymap, &e.key, e.modifiers) else {
+            return;
+        };
+        match action {
+            KeyAction::ToggleLock => handle_lock(),
+            KeyAction::ToggleGhost => handle_ghost(),
+            KeyAction::CycleWindowLevel => handle_level(),
+            KeyAction::NextProfile => handle_profile(),
+            KeyAction::ToggleCountdown => countdown.toggle_mode(),
+            KeyAction::StartStopTimer => countdown.toggle_timer(),
+            KeyAction::OpenSettings => show_settings.set(true),
+            KeyAction::ShowHelp => show_help.set(true),
+            KeyAction::Quit => platform.exit(),
+            KeyAction::NudgeLeft => handle_nudge(-NUDGE_STEP, 0.0),
+            KeyAction::NudgeRight => handle_nudge(NUDGE_STEP, 0.0),
+            KeyAction::NudgeUp => handle_nudge(0.0, -NUDGE_STEP),
+            KeyAction::NudgeDown => handle_nudge(0.0, NUDGE_STEP),
+            KeyAction::Grow => handle_resize(RESIZE_STEP),
+            KeyAction::Shrink => handle_resize(1.0 / RESIZE_STEP),
+        }
+    };
+
+    let mut handle_menu_action = move |action: MenuAction| match action {
+        MenuAction::ToggleLock => handle_lock(),
+        MenuAction::ToggleGhost => handle_ghost(),
+        MenuAction::CycleWindowLevel => handle_level(),
+        MenuAction::NextProfile => handle_profile(),
+        MenuAction::ToggleCountdown => countdown.toggle_mode(),
+        MenuAction::StartStopTimer => countdown.toggle_timer(),
+        MenuAction::ClearAlarm => countdown.clear_alarm(),
+        MenuAction::OpenSettings => show_settings.set(true),
+        MenuAction::ResetPosition => handle_reset_position(),
+        MenuAction::NextPositionPreset => handle_position_preset(),
+        MenuAction::Quit => platform.exit(),
+    };
+
+    use_http_server(app_config_context, handle_menu_action);
+
+    let window_opacity = if is_ghost {
+        app_conf().ghost_opacity
+    } else {
+        idle_fade.opacity()
+    };
+    // 日历提醒和倒计时结束时闪烁
+    let window_opacity = if flash.is_dimmed() {
+        window_opacity * FLASH_OPACITY
+    } else {
+        window_opacity
+    };
+
     rsx!(
         WindowDragArea {
-          enable: !app_conf().lock,
+          enable: !app_conf().lock.is_locked(),
           WindowDragResizeArea {
-            enable: !app_conf().lock,
+            enable: !app_conf().lock.is_locked(),
             aspect_ratio: RATIO,
+            snap,
+            snap_distance: app_conf().snap_distance,
             on_size_change: handle_size_change,
             rect {
               width: "100%",
               height: "100%",
               main_align: "center",
               cross_align: "center",
+              opacity: window_opacity.to_string(),
               onwindowmoved: handle_window_moved,
-              onmouseenter: move |_| { opacity.set("1") },
-              onmouseleave: move |_| { opacity.set("0") },
+              onmouseenter: move |_| {
+                  opacity.set("1");
+                  idle_fade.set_hovered(true);
+              },
+              onmouseleave: move |_| {
+                  opacity.set("0");
+                  idle_fade.set_hovered(false);
+              },
+              onkeydown,
+              onkeyup,
+              onrightclick: move |e: MouseEvent| {
+                  menu_position.set(Some(e.get_screen_coordinates().to_f32()))
+              },
               // border: "2 solid red",
               rect {
                 width: "100%",
@@ -68,7 +301,13 @@ pub fn MyApp() -> Element {
                 direction: "horizontal",
                 main_align: "center",
                 cross_align: "center",
-                MainArea{}
+                onclick: move |_| {
+                    // 有多个倒计时目标时点击数字切换
+                    if app_conf().mode == ClockMode::Countdown {
+                        countdown.next_target();
+                    }
+                },
+                MainArea{ countdown }
               }
               rect {
                 height: "1%",
@@ -81,11 +320,39 @@ pub fn MyApp() -> Element {
                 corner_radius: radius.to_string(),
                 corner_smoothing: "75%",
                 Tools {
-                    locked: app_conf().lock,
+                    locked: app_conf().lock.is_locked(),
+                    muted: app_conf().muted,
                     window_level: window_level,
+                    profile: app_conf().profile,
+                    label: countdown.label(),
+                    next_event: calendar.next_event(),
                     on_close_click: move |_| platform.exit(),
                     on_lock_click: move |_| handle_lock(),
                     on_level_click: move |_| handle_level(),
+                    on_mute_click: move |_| {
+                        let muted = app_conf().muted;
+                        app_conf.write().muted = !muted;
+                    },
+                    on_profile_click: move |_| handle_profile(),
+                    on_settings_click: move |_| show_settings.set(true),
+                }
+              }
+              if show_help() {
+                KeymapHelp {
+                  on_close: move |_| show_help.set(false),
+                }
+              }
+              // 设置面板只盖住左边，右边的数字继续显示，修改的效果可以直接看到
+              if show_settings() {
+                SettingsPanel {
+                  on_close: move |_| show_settings.set(false),
+                }
+              }
+              if let Some(position) = menu_position() {
+                ContextMenu {
+                  position,
+                  on_action: move |action| handle_menu_action(action),
+                  on_close: move |_| menu_position.set(None),
                 }
               }
             }
@@ -96,22 +363,48 @@ pub fn MyApp() -> Element {
 
 #[allow(non_snake_case)]
 #[component]
-pub fn MainArea() -> Element {
+pub fn MainArea(countdown: UseCountdown) -> Element {
+    let app_conf = use_app_conf_context().app_conf;
     let (hour, minute, second) = use_current_time();
+
+    // 倒计时显示 天:时:分，天数最多三位
+    let (groups, [first_group, middle_group, last_group]) = match countdown.digits() {
+        Some((days, hours, minutes)) => (
+            [(days, MAX_DAYS), (hours, 23), (minutes, 59)],
+            [DigitGroup::Days, DigitGroup::Hours, DigitGroup::Minutes],
+        ),
+        None => (
+            [(hour(), 23), (minute(), 59), (second(), 59)],
+            [DigitGroup::Hours, DigitGroup::Minutes, DigitGroup::Seconds],
+        ),
+    };
+    let [(first, first_max), (middle, middle_max), (last, last_max)] = groups;
+    let scale = layout_scale(&groups.map(|(_, max_num)| digit_count(max_num)));
+    let hide_leading_zeros = app_conf().hide_leading_zeros;
+
     rsx!(
         NumGroup {
-            num: hour(),
-            max_num: 23,
+            num: first,
+            max_num: first_max,
+            hide_leading_zeros,
+            scale,
+            group: first_group,
           }
-          Splitter{}
+          Splitter{ scale }
           NumGroup{
-            num: minute(),
-            max_num: 59,
+            num: middle,
+            max_num: middle_max,
+            hide_leading_zeros: false,
+            scale,
+            group: middle_group,
           }
-          Splitter{}
+          Splitter{ scale }
           NumGroup{
-            num: second(),
-            max_num: 59,
+            num: last,
+            max_num: last_max,
+            hide_leading_zeros: false,
+            scale,
+            group: last_group,
           }
     )
 }
@@ -0,0 +1,75 @@
+use serde_json::{Map, Value};
+
+use super::AppConfig;
+
+const ENV_PREFIX: &str = "FLIPCLOCK_";
+
+/// 从 `FLIPCLOCK_*` 环境变量读取配置项，例如 `FLIPCLOCK_CARD_COLOR` 对应 `card_color`
+///
+/// 取值按照默认配置中对应字段的类型解析，解析失败的环境变量会返回错误说明
+pub fn read_env_layer() -> Result<Map<String, Value>, Vec<String>> {
+    let defaults = serde_json::to_value(AppConfig::default()).unwrap();
+    let mut layer = Map::new();
+    let mut errors = vec![];
+
+    for (key, default_value) in defaults.as_object().unwrap() {
+        if key == "version" {
+            continue;
+        }
+        let name = format!("{ENV_PREFIX}{}", key.to_uppercase());
+        let Ok(raw) = std::env::var(&name) else {
+            continue;
+        };
+
+        match parse_env_value(&raw, default_value) {
+            Some(value) => {
+                layer.insert(key.clone(), value);
+            }
+            None => errors.push(format!("{name}: invalid value \"{raw}\"")),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut app_conf = serde_json::to_value(AppConfig::default()).unwrap();
+    app_conf.as_object_mut().unwrap().extend(layer.clone());
+    serde_json::from_value::<AppConfig>(app_conf)
+        .map_err(|e| vec![e.to_string()])?
+        .validate()
+        .map_err(|errors| {
+            errors
+                .into_iter()
+                .map(|error| match error.split_once(':') {
+                    Some((key, message)) => {
+                        format!("{ENV_PREFIX}{}:{message}", key.to_uppercase())
+                    }
+                    None => error,
+                })
+                .collect::<Vec<_>>()
+        })?;
+
+    Ok(layer)
+}
+
+fn parse_env_value(raw: &str, default_value: &Value) -> Option<Value> {
+    let raw = raw.trim();
+    match default_value {
+        Value::String(_) => Some(Value::String(raw.to_owned())),
+        // 可选的配置项，JSON 对象按对象解析，其余的当作字符串
+        Value::Null => Some(
+            serde_json::from_str::<Map<String, Value>>(raw)
+                .map(Value::Object)
+                .unwrap_or_else(|_| Value::String(raw.to_owned())),
+        ),
+        Value::Bool(_) => match raw.to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Some(Value::Bool(true)),
+            "0" | "false" | "no" | "off" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        Value::Number(number) if number.is_f64() => raw.parse::<f64>().ok().map(Value::from),
+        Value::Number(_) => raw.parse::<i64>().ok().map(Value::from),
+        _ => serde_json::from_str(raw).ok(),
+    }
+}
@@ -0,0 +1,103 @@
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::ConfigError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// 根据文件后缀选择配置格式，未知的后缀按 JSON 处理
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(ConfigFormat::Json),
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Yaml => "yaml",
+        }
+    }
+
+    pub fn parse(&self, config_str: &str) -> Result<Value, ConfigError> {
+        match self {
+            ConfigFormat::Json => serde_json::from_str(config_str).map_err(syntax_error),
+            ConfigFormat::Toml => toml::from_str(config_str).map_err(syntax_error),
+            ConfigFormat::Yaml => serde_yaml::from_str(config_str).map_err(syntax_error),
+        }
+    }
+
+    pub fn serialize(&self, value: &impl Serialize) -> Result<String, ConfigError> {
+        match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(value).map_err(serialize_error),
+            // TOML 不能表示 null，值为空的配置项不写入，读取时使用默认值
+            ConfigFormat::Toml => {
+                let mut value = serde_json::to_value(value).map_err(serialize_error)?;
+                remove_nulls(&mut value);
+                toml::to_string_pretty(&value).map_err(serialize_error)
+            }
+            ConfigFormat::Yaml => serde_yaml::to_string(value).map_err(serialize_error),
+        }
+    }
+}
+
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, value| !value.is_null());
+            map.values_mut().for_each(remove_nulls);
+        }
+        Value::Array(values) => values.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+fn syntax_error(e: impl std::fmt::Display) -> ConfigError {
+    ConfigError::Syntax(e.to_string())
+}
+
+fn serialize_error(e: impl std::fmt::Display) -> ConfigError {
+    ConfigError::Serialize(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_config::AppConfig;
+
+    #[test]
+    fn default_config_round_trips_in_every_format() {
+        let app_conf = AppConfig::default();
+        for conf_format in [ConfigFormat::Json, ConfigFormat::Toml, ConfigFormat::Yaml] {
+            let config_str = app_conf.to_string(conf_format).unwrap();
+            let (parsed, _) = AppConfig::try_parse(&config_str, conf_format).unwrap();
+            assert_eq!(parsed, app_conf, "{conf_format:?}");
+        }
+    }
+}
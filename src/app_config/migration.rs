@@ -0,0 +1,100 @@
+use serde_json::{Map, Value};
+
+pub const CONFIG_VERSION: u32 = 1;
+
+type Migration = fn(&mut Map<String, Value>);
+
+// MIGRATIONS[n] 把版本 n 的配置升级到版本 n + 1
+const MIGRATIONS: [Migration; CONFIG_VERSION as usize] = [migrate_v0_to_v1];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MigrateError {
+    NotAnObject,
+    UnsupportedVersion(u32),
+}
+
+/// 读取配置的版本号，没有 `version` 字段的是最早的版本 0
+pub fn config_version(value: &Value) -> u32 {
+    value
+        .get("version")
+        .and_then(Value::as_u64)
+        .map(|version| version as u32)
+        .unwrap_or(0)
+}
+
+/// 把任意历史版本的配置逐级升级到 [`CONFIG_VERSION`]
+pub fn migrate(mut value: Value) -> Result<Value, MigrateError> {
+    let version = config_version(&value);
+    if version > CONFIG_VERSION {
+        return Err(MigrateError::UnsupportedVersion(version));
+    }
+
+    let map = value.as_object_mut().ok_or(MigrateError::NotAnObject)?;
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(map);
+    }
+
+    Ok(value)
+}
+
+// 版本 0 是最早没有 version 字段的配置。它的 lock 是布尔值，由 LockMode 直接解析；size 是物理像素，
+// 没有 monitor 字段，启动之后再换算。之后增加的字段都由默认值补齐
+fn migrate_v0_to_v1(map: &mut Map<String, Value>) {
+    map.insert("version".to_owned(), 1.into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_config::{AppConfig, ConfigFormat, LockMode};
+
+    // FIXTURES[n] 是版本 n 的程序写出的配置文件
+    const FIXTURES: [&str; CONFIG_VERSION as usize + 1] = [
+        include_str!("fixtures/v0.json"),
+        include_str!("fixtures/v1.json"),
+    ];
+
+    #[test]
+    fn every_fixture_loads() {
+        for (version, fixture) in FIXTURES.iter().enumerate() {
+            let (app_conf, from_version) = AppConfig::try_parse(fixture, ConfigFormat::Json)
+                .unwrap_or_else(|e| panic!("v{version}: {e}"));
+            assert_eq!(from_version, version as u32);
+            assert_eq!(app_conf.version, CONFIG_VERSION);
+            assert_eq!(app_conf.lock, LockMode::Locked, "v{version}");
+            assert_eq!(app_conf.window_level, 1, "v{version}");
+            assert_eq!(app_conf.validate(), Ok(()), "v{version}");
+        }
+    }
+
+    #[test]
+    fn every_migration_step_loads() {
+        for (version, fixture) in FIXTURES.iter().enumerate() {
+            let mut value = serde_json::from_str::<Value>(fixture).unwrap();
+            for (step, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+                migration(value.as_object_mut().unwrap());
+                assert_eq!(config_version(&value), step as u32 + 1);
+
+                let config_str = serde_json::to_string(&value).unwrap();
+                if let Err(e) = AppConfig::try_parse(&config_str, ConfigFormat::Json) {
+                    panic!("v{version} after migrate_v{step}_to_v{}: {e}", step + 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn current_version_is_unchanged() {
+        let value = serde_json::from_str::<Value>(FIXTURES[CONFIG_VERSION as usize]).unwrap();
+        assert_eq!(migrate(value.clone()), Ok(value));
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let value = serde_json::json!({ "version": CONFIG_VERSION + 1 });
+        assert_eq!(
+            migrate(value),
+            Err(MigrateError::UnsupportedVersion(CONFIG_VERSION + 1))
+        );
+    }
+}
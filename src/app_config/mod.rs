@@ -0,0 +1,758 @@
+mod env;
+mod format;
+mod migration;
+mod overrides;
+mod profile;
+
+use freya::prelude::{Size2D, WindowLevel};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use skia_safe::Color;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::SystemTime;
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    calendar::CalendarConfig,
+    chime::ChimeConfig,
+    click_sound::ClickSoundConfig,
+    colors::Parse,
+    constant::{MAX_FLIP_DURATION, RATIO},
+    countdown::CountdownConfig,
+    event_hooks::HookEvent,
+    keymap::{effective_keymap, find_conflicts, KeyAction},
+    placement::{MonitorIdentity, PositionPreset},
+};
+
+pub use env::read_env_layer;
+pub use format::ConfigFormat;
+pub use migration::{MigrateError, CONFIG_VERSION};
+pub use overrides::ConfigOverrides;
+pub use profile::Profile;
+
+// 通过命令行指定的配置文件路径
+static CONF_PATH: OnceLock<String> = OnceLock::new();
+// 环境变量中的配置项，优先级在默认值和配置文件之间
+static ENV_LAYER: OnceLock<Map<String, Value>> = OnceLock::new();
+
+// 按顺序查找已存在的配置文件，都不存在时使用第一个
+const CONF_PATHS: [&str; 4] = [
+    "./FlipClock.json",
+    "./FlipClock.toml",
+    "./FlipClock.yaml",
+    "./FlipClock.yml",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AppConfig {
+    pub version: u32,
+    pub dot_color: String,
+    pub card_color: String,
+    pub font_color: String,
+    // 窗口宽度，单位是逻辑像素，在不同缩放比例的显示器上看起来一样大
+    pub size: f64,
+    pub x: i32,
+    pub y: i32,
+    pub lock: LockMode,
+    pub window_level: u32,
+    // 整个窗口的不透明度
+    pub opacity: f32,
+    // 空闲多少秒之后渐隐，0 表示不渐隐
+    pub idle_timeout: u64,
+    // 空闲时整个窗口的不透明度
+    pub idle_opacity: f32,
+    // 幽灵模式下整个窗口的不透明度
+    pub ghost_opacity: f32,
+    pub font_family: String,
+    // 翻页动画的时长，单位毫秒
+    pub flip_duration: u64,
+    // 翻页动画结束时是否回弹
+    pub flip_bounce: bool,
+    // 小时或者倒计时天数的高位 0 显示为空白
+    pub hide_leading_zeros: bool,
+    // 拖动和调整大小时吸附到边缘的距离，单位是物理像素，0 表示不吸附
+    pub snap_distance: f32,
+    // 本机 HTTP 状态接口的端口，0 表示不启动
+    pub http_port: u16,
+    // 相对于显示器的预设位置，设置之后启动和显示器变化时按它摆放窗口，拖动窗口后清除
+    pub position_preset: Option<PositionPreset>,
+    // 上次记录大小时窗口所在的显示器，为空时 size 是旧版本使用的物理像素
+    pub monitor: Option<MonitorIdentity>,
+    // 覆盖默认的快捷键，值为空字符串时取消绑定
+    pub keymap: BTreeMap<KeyAction, String>,
+    // 整点和刻钟报时
+    pub chime: ChimeConfig,
+    // 数字翻动时的声音
+    pub click_sound: ClickSoundConfig,
+    // 关闭所有声音
+    pub muted: bool,
+    // 从本地的日历文件中读取接下来的事件
+    pub calendar: CalendarConfig,
+    // 显示时钟还是倒计时
+    pub mode: ClockMode,
+    // 倒计时的目标时间
+    pub countdown: CountdownConfig,
+    // 事件发生时运行的命令，例如 on_hour、on_lock_change
+    pub event_hooks: BTreeMap<HookEvent, String>,
+    // 事件命令运行的最长时间，单位秒，超时之后结束命令
+    pub hook_timeout: u64,
+    // 当前使用的配置方案
+    pub profile: Option<String>,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            dot_color: "#cccccc".to_string(),
+            card_color: "#191919".to_string(),
+            font_color: "#cccccc".to_string(),
+            size: 700.0,
+            x: 100,
+            y: 100,
+            lock: LockMode::Unlocked,
+            window_level: 0,
+            opacity: 1.0,
+            idle_timeout: 0,
+            idle_opacity: 0.3,
+            ghost_opacity: 0.4,
+            font_family: "Times New Roman".to_string(),
+            flip_duration: 500,
+            flip_bounce: true,
+            hide_leading_zeros: false,
+            snap_distance: 16.0,
+            http_port: 0,
+            position_preset: None,
+            monitor: None,
+            keymap: BTreeMap::new(),
+            chime: ChimeConfig::default(),
+            click_sound: ClickSoundConfig::default(),
+            muted: false,
+            calendar: CalendarConfig::default(),
+            mode: ClockMode::Clock,
+            countdown: CountdownConfig::default(),
+            event_hooks: BTreeMap::new(),
+            hook_timeout: 30,
+            profile: None,
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// 在缩放比例为 `scale_factor` 的显示器上，窗口的物理大小
+    pub fn window_size(&self, scale_factor: f32) -> Size2D {
+        let width = self.size as f32 * scale_factor;
+        Size2D::new(width, width / RATIO)
+    }
+
+    /// 把旧版本以物理像素记录的大小和边距换算成逻辑像素，配置方案中的大小一起换算
+    ///
+    /// 命令行和环境变量给出的值本来就是逻辑像素，`keep_size` 和 `keep_preset` 为 true 时
+    /// 对应的配置项保持不变。
+    pub fn convert_to_logical_size(
+        &mut self,
+        monitor: MonitorIdentity,
+        keep_size: bool,
+        keep_preset: bool,
+    ) {
+        let scale_factor = monitor.scale_factor as f64;
+        if !keep_size {
+            self.size /= scale_factor;
+        }
+        if let (false, Some(preset)) = (keep_preset, &mut self.position_preset) {
+            preset.margin /= monitor.scale_factor;
+        }
+        for profile in self.profiles.values_mut() {
+            profile.size /= scale_factor;
+        }
+        self.monitor = Some(monitor);
+    }
+
+    pub fn set_conf_path(conf_path: String) {
+        let _ = CONF_PATH.set(conf_path);
+    }
+
+    pub fn get_conf_path() -> String {
+        if let Some(conf_path) = CONF_PATH.get() {
+            return conf_path.clone();
+        }
+        CONF_PATHS
+            .iter()
+            .find(|path| Path::new(path).exists())
+            .unwrap_or(&CONF_PATHS[0])
+            .to_string()
+    }
+
+    pub fn set_env_layer(env_layer: Map<String, Value>) {
+        let _ = ENV_LAYER.set(env_layer);
+    }
+
+    /// 在默认配置上叠加一层配置项，例如环境变量
+    ///
+    /// 无法解析的配置项记录警告之后跳过，其余的配置项仍然生效。
+    pub fn from_layer(layer: Map<String, Value>) -> Self {
+        let mut app_conf = AppConfig::default();
+        let mut valid = Map::new();
+        for (key, value) in layer {
+            let mut candidate = valid.clone();
+            candidate.insert(key.clone(), value.clone());
+            match serde_json::from_value::<AppConfig>(Value::Object(candidate)) {
+                Ok(parsed) => {
+                    app_conf = parsed;
+                    valid.insert(key, value);
+                }
+                Err(e) => log::warn!("ignoring config {key} = {value}: {e}"),
+            }
+        }
+        app_conf
+    }
+
+    /// 配置项的值来自环境变量，配置文件中没有它
+    pub fn is_from_env(key: &str) -> bool {
+        ENV_LAYER
+            .get()
+            .is_some_and(|env_layer| env_layer.contains_key(key))
+            && !Self::file_keys().iter().any(|file_key| file_key == key)
+    }
+
+    pub fn get_conf_format() -> ConfigFormat {
+        ConfigFormat::from_path(Self::get_conf_path())
+    }
+
+    /// 读取配置文件，旧版本的配置升级之后写回文件
+    ///
+    /// 文件不存在时创建默认的配置文件。文件无法解析或者版本比程序新时返回错误，不改动文件。
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_from(Self::get_conf_path())
+    }
+
+    pub fn load_from(conf_path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let conf_path = conf_path.as_ref();
+        let conf_format = ConfigFormat::from_path(conf_path);
+
+        let write_file = |app_config: &AppConfig| {
+            let result = app_config
+                .to_file_string(conf_format, &Self::file_keys_of(conf_path))
+                .and_then(|config_str| {
+                    std::fs::write(conf_path, config_str).map_err(ConfigError::from)
+                });
+            if let Err(e) = result {
+                log::warn!("failed to write {}: {e}", conf_path.display());
+            }
+        };
+
+        let config_str = match std::fs::read_to_string(conf_path) {
+            Ok(config_str) => config_str,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                // 环境变量中的配置项写入文件时会被去掉，以后仍然由环境变量决定
+                let app_conf = AppConfig::from_layer(ENV_LAYER.get().cloned().unwrap_or_default());
+                write_file(&app_conf);
+                return Ok(app_conf);
+            }
+            Err(e) => return Err(ConfigError::Io(e.to_string())),
+        };
+
+        let (app_conf, from_version) = Self::try_parse(&config_str, conf_format)?;
+        if from_version < CONFIG_VERSION {
+            write_file(&app_conf);
+        }
+        Ok(app_conf)
+    }
+
+    pub async fn save(&self) {
+        self.save_to(Self::get_conf_path()).await;
+    }
+
+    pub async fn save_to(&self, conf_path: impl AsRef<Path>) {
+        let conf_format = ConfigFormat::from_path(&conf_path);
+        let file_keys = Self::file_keys_of(&conf_path);
+        let config_str = match self.to_file_string(conf_format, &file_keys) {
+            Ok(config_str) => config_str,
+            Err(e) => {
+                log::warn!("failed to save the config: {e}");
+                return;
+            }
+        };
+        let conf_file = tokio::fs::File::create(conf_path).await;
+        if let Ok(mut conf_file) = conf_file {
+            conf_file.write_all(config_str.as_bytes()).await.unwrap();
+        }
+    }
+
+    pub async fn read() -> Option<Self> {
+        Self::read_from(Self::get_conf_path()).await
+    }
+
+    pub async fn read_from(conf_path: impl AsRef<Path>) -> Option<Self> {
+        let conf_format = ConfigFormat::from_path(&conf_path);
+        let config_str = tokio::fs::read_to_string(conf_path).await.ok()?;
+        Self::parse(&config_str, conf_format).map(|(app_conf, _)| app_conf)
+    }
+
+    pub async fn modified_time() -> Option<SystemTime> {
+        tokio::fs::metadata(Self::get_conf_path())
+            .await
+            .ok()?
+            .modified()
+            .ok()
+    }
+
+    /// 把当前的配置文件转换成另一种格式，并删除原来的文件，返回新文件的路径
+    ///
+    /// 直接解析原来的文件，不会升级或者创建它。新文件写入并且读回确认内容一致之后才删除原来的文件。
+    pub fn convert(conf_format: ConfigFormat) -> Result<String, ConfigError> {
+        let old_conf_path = Self::get_conf_path();
+        let new_conf_path = Path::new(&old_conf_path)
+            .with_extension(conf_format.extension())
+            .to_string_lossy()
+            .to_string();
+        if old_conf_path == new_conf_path {
+            return Ok(new_conf_path);
+        }
+        if Path::new(&new_conf_path).exists() {
+            return Err(ConfigError::Io(format!("{new_conf_path} already exists")));
+        }
+
+        let config_str = std::fs::read_to_string(&old_conf_path)?;
+        let (app_conf, _) = Self::try_parse(&config_str, ConfigFormat::from_path(&old_conf_path))?;
+        let file_keys = Self::file_keys_of(&old_conf_path);
+        std::fs::write(
+            &new_conf_path,
+            app_conf.to_file_string(conf_format, &file_keys)?,
+        )?;
+
+        let written = std::fs::read_to_string(&new_conf_path)?;
+        let read_back = Self::try_parse(&written, conf_format).map(|(app_conf, _)| app_conf);
+        if read_back.as_ref().ok() != Some(&app_conf) {
+            let _ = std::fs::remove_file(&new_conf_path);
+            return Err(ConfigError::Io(format!(
+                "{new_conf_path} does not read back as the original config"
+            )));
+        }
+
+        std::fs::remove_file(old_conf_path)?;
+        Ok(new_conf_path)
+    }
+
+    pub fn parse(config_str: &str, conf_format: ConfigFormat) -> Option<(Self, u32)> {
+        Self::try_parse(config_str, conf_format).ok()
+    }
+
+    /// 解析任意版本的配置，返回升级后的配置和原始的版本号
+    pub fn try_parse(
+        config_str: &str,
+        conf_format: ConfigFormat,
+    ) -> Result<(Self, u32), ConfigError> {
+        let value = conf_format.parse(config_str)?;
+        let from_version = migration::config_version(&value);
+        let value = migration::migrate(value).map_err(ConfigError::Migrate)?;
+
+        // 配置文件中没有的字段使用环境变量中的值
+        let mut layered = ENV_LAYER.get().cloned().unwrap_or_default();
+        if let Value::Object(map) = value {
+            layered.extend(map);
+        }
+
+        let app_conf = serde_json::from_value::<AppConfig>(Value::Object(layered))
+            .map_err(|e| ConfigError::Syntax(e.to_string()))?;
+        Ok((app_conf, from_version))
+    }
+
+    /// 配置文件中实际存在的字段
+    pub fn file_keys() -> Vec<String> {
+        Self::file_keys_of(Self::get_conf_path())
+    }
+
+    fn file_keys_of(conf_path: impl AsRef<Path>) -> Vec<String> {
+        let conf_path = conf_path.as_ref();
+        let conf_format = ConfigFormat::from_path(conf_path);
+        std::fs::read_to_string(conf_path)
+            .ok()
+            .and_then(|config_str| conf_format.parse(&config_str).ok())
+            .and_then(|value| migration::migrate(value).ok())
+            .and_then(|value| value.as_object().map(|map| map.keys().cloned().collect()))
+            .unwrap_or_default()
+    }
+
+    /// 在 debug 日志中输出每个配置项最终的取值来源
+    pub fn log_sources(&self, overrides: &ConfigOverrides) {
+        let file_keys = Self::file_keys();
+        let env_layer = ENV_LAYER.get();
+        let override_keys = overrides.keys();
+        let Value::Object(map) = serde_json::to_value(self).unwrap() else {
+            return;
+        };
+
+        for (key, value) in map {
+            let source = if override_keys.contains(&key.as_str()) {
+                "command line"
+            } else if file_keys.contains(&key) {
+                "config file"
+            } else if env_layer.is_some_and(|env_layer| env_layer.contains_key(&key)) {
+                "environment"
+            } else {
+                "default"
+            };
+            log::debug!("config {key} = {value} (from {source})");
+        }
+    }
+
+    /// 检查配置项的取值是否合法，返回所有不合法的配置项说明
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+
+        for (name, color) in [
+            ("dot_color", &self.dot_color),
+            ("card_color", &self.card_color),
+            ("font_color", &self.font_color),
+        ] {
+            if Color::parse(color).is_err() {
+                errors.push(format!("{name}: invalid color \"{color}\""));
+            }
+        }
+        if !self.size.is_finite() || self.size <= 0.0 {
+            errors.push(format!("size: must be a positive number, got {}", self.size));
+        }
+        if self.window_level > 2 {
+            errors.push(format!(
+                "window_level: must be 0, 1 or 2, got {}",
+                self.window_level
+            ));
+        }
+        for (name, opacity) in [
+            ("opacity", self.opacity),
+            ("idle_opacity", self.idle_opacity),
+            ("ghost_opacity", self.ghost_opacity),
+        ] {
+            if !(0.0..=1.0).contains(&opacity) {
+                errors.push(format!("{name}: must be between 0 and 1, got {opacity}"));
+            }
+        }
+        if self.font_family.trim().is_empty() {
+            errors.push("font_family: must not be empty".to_string());
+        }
+        if self.flip_duration > MAX_FLIP_DURATION {
+            errors.push(format!(
+                "flip_duration: must be at most {MAX_FLIP_DURATION}, got {}",
+                self.flip_duration
+            ));
+        }
+        if !self.snap_distance.is_finite() || self.snap_distance < 0.0 {
+            errors.push(format!(
+                "snap_distance: must not be negative, got {}",
+                self.snap_distance
+            ));
+        }
+        if let Some(preset) = self.position_preset {
+            if !preset.margin.is_finite() || preset.margin < 0.0 {
+                errors.push(format!(
+                    "position_preset.margin: must not be negative, got {}",
+                    preset.margin
+                ));
+            }
+        }
+        for (key, actions) in find_conflicts(&effective_keymap(&self.keymap)) {
+            let actions = actions
+                .iter()
+                .map(|action| serde_json::to_string(action).unwrap())
+                .collect::<Vec<_>>()
+                .join(", ");
+            errors.push(format!("keymap: {key} is bound to {actions}"));
+        }
+        errors.extend(self.chime.validate());
+        errors.extend(self.click_sound.validate());
+        errors.extend(self.calendar.validate());
+        errors.extend(self.countdown.validate());
+        if self.hook_timeout == 0 {
+            errors.push("hook_timeout: must be at least 1 second".to_string());
+        }
+        if let Some(profile) = &self.profile {
+            if !self.profiles.contains_key(profile) {
+                errors.push(format!("profile: unknown profile \"{profile}\""));
+            }
+        }
+        for (name, profile) in &self.profiles {
+            let mut app_conf = AppConfig::default();
+            profile.to_overrides().apply(&mut app_conf);
+            if let Err(profile_errors) = app_conf.validate() {
+                errors.extend(
+                    profile_errors
+                        .into_iter()
+                        .map(|error| format!("profiles.{name}.{error}")),
+                );
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn to_string(&self, conf_format: ConfigFormat) -> Result<String, ConfigError> {
+        conf_format.serialize(self)
+    }
+
+    /// 写入配置文件的内容
+    ///
+    /// `file_keys` 是文件中原有的字段，它们总是写入。文件中没有、取值仍然等于环境变量的字段不写入，
+    /// 以免配置文件覆盖环境变量。
+    pub fn to_file_string(
+        &self,
+        conf_format: ConfigFormat,
+        file_keys: &[String],
+    ) -> Result<String, ConfigError> {
+        let mut value =
+            serde_json::to_value(self).map_err(|e| ConfigError::Serialize(e.to_string()))?;
+        if let (Some(env_layer), Value::Object(map)) = (ENV_LAYER.get(), &mut value) {
+            map.retain(|key, value| file_keys.contains(key) || env_layer.get(key) != Some(&*value));
+        }
+        conf_format.serialize(&value)
+    }
+}
+
+/// 数字显示的内容
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockMode {
+    #[default]
+    Clock,
+    // 到目标时间还有多少天、小时和分钟
+    Countdown,
+}
+
+impl ClockMode {
+    pub fn toggle(self) -> Self {
+        match self {
+            ClockMode::Clock => ClockMode::Countdown,
+            ClockMode::Countdown => ClockMode::Clock,
+        }
+    }
+}
+
+/// 窗口的锁定状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockMode {
+    #[default]
+    Unlocked,
+    // 不能拖动和调整大小
+    Locked,
+    // 在锁定的基础上不响应鼠标，点击会穿透到下面的窗口
+    Ghost,
+}
+
+impl LockMode {
+    pub fn from_bool(locked: bool) -> Self {
+        if locked {
+            LockMode::Locked
+        } else {
+            LockMode::Unlocked
+        }
+    }
+
+    pub fn is_locked(self) -> bool {
+        self != LockMode::Unlocked
+    }
+
+    /// 锁定和解锁之间切换，幽灵模式切换到普通的锁定
+    pub fn toggle_lock(self) -> Self {
+        match self {
+            LockMode::Unlocked => LockMode::Locked,
+            LockMode::Locked => LockMode::Unlocked,
+            LockMode::Ghost => LockMode::Locked,
+        }
+    }
+
+    /// 进入或者退出幽灵模式，退出之后仍然保持锁定
+    pub fn toggle_ghost(self) -> Self {
+        match self {
+            LockMode::Ghost => LockMode::Locked,
+            _ => LockMode::Ghost,
+        }
+    }
+}
+
+// 旧版本的 lock 是布尔值，环境变量中也习惯写 true 或 1，它们都表示普通的锁定
+impl<'de> Deserialize<'de> for LockMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawLockMode {
+            Bool(bool),
+            Number(u64),
+            Text(String),
+        }
+
+        let text = match RawLockMode::deserialize(deserializer)? {
+            RawLockMode::Bool(locked) => return Ok(LockMode::from_bool(locked)),
+            RawLockMode::Number(number) => number.to_string(),
+            RawLockMode::Text(text) => text,
+        };
+        match text.trim().to_ascii_lowercase().as_str() {
+            "unlocked" | "false" | "0" => Ok(LockMode::Unlocked),
+            "locked" | "true" | "1" => Ok(LockMode::Locked),
+            "ghost" => Ok(LockMode::Ghost),
+            _ => Err(de::Error::unknown_variant(
+                &text,
+                &["unlocked", "locked", "ghost", "true", "false", "1", "0"],
+            )),
+        }
+    }
+}
+
+pub fn to_window_level(level: u32) -> WindowLevel {
+    match level {
+        1 => WindowLevel::AlwaysOnTop,
+        2 => WindowLevel::AlwaysOnBottom,
+        _ => WindowLevel::Normal,
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(String),
+    Syntax(String),
+    Serialize(String),
+    Migrate(MigrateError),
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {
+        ConfigError::Io(e.to_string())
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "{e}"),
+            ConfigError::Syntax(e) => write!(f, "syntax error: {e}"),
+            ConfigError::Serialize(e) => write!(f, "failed to serialize: {e}"),
+            ConfigError::Migrate(MigrateError::NotAnObject) => {
+                write!(f, "the config must be a table of key/value pairs")
+            }
+            ConfigError::Migrate(MigrateError::UnsupportedVersion(version)) => {
+                write!(f, "config version {version} is newer than {CONFIG_VERSION}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn temp_conf_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("flip_clock_{}_{name}", std::process::id()))
+    }
+
+    #[test]
+    fn load_keeps_unparsable_config() {
+        let conf_path = temp_conf_path("unparsable.json");
+        std::fs::write(&conf_path, "{ \"size\": ").unwrap();
+
+        let result = AppConfig::load_from(&conf_path);
+        assert!(matches!(result, Err(ConfigError::Syntax(_))));
+        assert_eq!(std::fs::read_to_string(&conf_path).unwrap(), "{ \"size\": ");
+        std::fs::remove_file(&conf_path).unwrap();
+    }
+
+    #[test]
+    fn load_keeps_newer_config() {
+        let conf_path = temp_conf_path("newer.json");
+        let config_str = format!("{{ \"version\": {} }}", CONFIG_VERSION + 1);
+        std::fs::write(&conf_path, &config_str).unwrap();
+
+        let result = AppConfig::load_from(&conf_path);
+        assert!(matches!(
+            result,
+            Err(ConfigError::Migrate(MigrateError::UnsupportedVersion(_)))
+        ));
+        assert_eq!(std::fs::read_to_string(&conf_path).unwrap(), config_str);
+        std::fs::remove_file(&conf_path).unwrap();
+    }
+
+    #[test]
+    fn load_upgrades_old_config() {
+        let conf_path = temp_conf_path("old.json");
+        std::fs::write(&conf_path, include_str!("fixtures/v0.json")).unwrap();
+
+        let app_conf = AppConfig::load_from(&conf_path).unwrap();
+        assert_eq!(app_conf.version, CONFIG_VERSION);
+        let config_str = std::fs::read_to_string(&conf_path).unwrap();
+        let (saved, from_version) = AppConfig::try_parse(&config_str, ConfigFormat::Json).unwrap();
+        assert_eq!(from_version, CONFIG_VERSION);
+        assert_eq!(saved, app_conf);
+        std::fs::remove_file(&conf_path).unwrap();
+    }
+
+    #[test]
+    fn lock_accepts_booleans_and_numbers() {
+        for (value, expected) in [
+            (serde_json::json!("unlocked"), LockMode::Unlocked),
+            (serde_json::json!("locked"), LockMode::Locked),
+            (serde_json::json!("ghost"), LockMode::Ghost),
+            (serde_json::json!(true), LockMode::Locked),
+            (serde_json::json!(false), LockMode::Unlocked),
+            (serde_json::json!(1), LockMode::Locked),
+            (serde_json::json!(0), LockMode::Unlocked),
+            (serde_json::json!("true"), LockMode::Locked),
+            (serde_json::json!("0"), LockMode::Unlocked),
+        ] {
+            let lock = serde_json::from_value::<LockMode>(value.clone());
+            assert_eq!(lock.ok(), Some(expected), "{value}");
+        }
+
+        assert!(serde_json::from_value::<LockMode>(serde_json::json!("maybe")).is_err());
+        assert!(serde_json::from_value::<LockMode>(serde_json::json!(2)).is_err());
+    }
+
+    #[test]
+    fn layer_overrides_defaults() {
+        let layer = serde_json::json!({ "card_color": "#102030", "size": 300.0 });
+        let Value::Object(layer) = layer else {
+            unreachable!()
+        };
+
+        let app_conf = AppConfig::from_layer(layer);
+        assert_eq!(app_conf.card_color, "#102030");
+        assert_eq!(app_conf.size, 300.0);
+        assert_eq!(app_conf.dot_color, AppConfig::default().dot_color);
+    }
+
+    #[test]
+    fn layer_skips_invalid_values() {
+        let layer = serde_json::json!({ "card_color": "#102030", "size": "big", "lock": "ghost" });
+        let Value::Object(layer) = layer else {
+            unreachable!()
+        };
+
+        let app_conf = AppConfig::from_layer(layer);
+        assert_eq!(app_conf.card_color, "#102030");
+        assert_eq!(app_conf.lock, LockMode::Ghost);
+        assert_eq!(app_conf.size, AppConfig::default().size);
+    }
+
+    #[test]
+    fn load_creates_missing_config() {
+        let conf_path = temp_conf_path("missing.toml");
+        let _ = std::fs::remove_file(&conf_path);
+
+        let app_conf = AppConfig::load_from(&conf_path).unwrap();
+        assert_eq!(app_conf, AppConfig::default());
+        let config_str = std::fs::read_to_string(&conf_path).unwrap();
+        assert!(AppConfig::try_parse(&config_str, ConfigFormat::Toml).is_ok());
+        std::fs::remove_file(&conf_path).unwrap();
+    }
+}
@@ -0,0 +1,156 @@
+use super::{AppConfig, ClockMode, LockMode};
+use crate::placement::PositionPreset;
+
+/// 只在本次运行中生效、不写入配置文件的配置项
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigOverrides {
+    pub dot_color: Option<String>,
+    pub card_color: Option<String>,
+    pub font_color: Option<String>,
+    pub size: Option<f64>,
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub lock: Option<LockMode>,
+    pub window_level: Option<u32>,
+    pub mode: Option<ClockMode>,
+    pub hide_leading_zeros: Option<bool>,
+    pub position_preset: Option<PositionPreset>,
+    pub profile: Option<String>,
+}
+
+impl ConfigOverrides {
+    /// 覆盖项和配置文件使用同样的规则检查
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut app_conf = AppConfig::default();
+        self.apply(&mut app_conf);
+        app_conf.validate()
+    }
+
+    /// 被覆盖的配置项名称
+    pub fn keys(&self) -> Vec<&'static str> {
+        [
+            ("dot_color", self.dot_color.is_some()),
+            ("card_color", self.card_color.is_some()),
+            ("font_color", self.font_color.is_some()),
+            ("size", self.size.is_some()),
+            ("x", self.x.is_some()),
+            ("y", self.y.is_some()),
+            ("lock", self.lock.is_some()),
+            ("window_level", self.window_level.is_some()),
+            ("mode", self.mode.is_some()),
+            ("hide_leading_zeros", self.hide_leading_zeros.is_some()),
+            ("position_preset", self.position_preset.is_some()),
+            ("profile", self.profile.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(key, is_some)| is_some.then_some(key))
+        .collect()
+    }
+
+    /// 用 `other` 中给出的覆盖项替换同名的覆盖项
+    pub fn merge(&mut self, other: ConfigOverrides) {
+        merge_field(&mut self.dot_color, other.dot_color);
+        merge_field(&mut self.card_color, other.card_color);
+        merge_field(&mut self.font_color, other.font_color);
+        merge_field(&mut self.size, other.size);
+        merge_field(&mut self.x, other.x);
+        merge_field(&mut self.y, other.y);
+        merge_field(&mut self.lock, other.lock);
+        merge_field(&mut self.window_level, other.window_level);
+        merge_field(&mut self.mode, other.mode);
+        merge_field(&mut self.hide_leading_zeros, other.hide_leading_zeros);
+        merge_field(&mut self.position_preset, other.position_preset);
+        merge_field(&mut self.profile, other.profile);
+    }
+
+    /// 删除 `other` 中给出的覆盖项，这些配置项之后写入配置文件
+    pub fn remove(&mut self, other: &ConfigOverrides) {
+        remove_field(&mut self.dot_color, &other.dot_color);
+        remove_field(&mut self.card_color, &other.card_color);
+        remove_field(&mut self.font_color, &other.font_color);
+        remove_field(&mut self.size, &other.size);
+        remove_field(&mut self.x, &other.x);
+        remove_field(&mut self.y, &other.y);
+        remove_field(&mut self.lock, &other.lock);
+        remove_field(&mut self.window_level, &other.window_level);
+        remove_field(&mut self.mode, &other.mode);
+        remove_field(&mut self.hide_leading_zeros, &other.hide_leading_zeros);
+        remove_field(&mut self.position_preset, &other.position_preset);
+        remove_field(&mut self.profile, &other.profile);
+    }
+
+    pub fn apply(&self, app_conf: &mut AppConfig) {
+        // 先应用配置方案，其余的覆盖项优先级更高
+        if let Some(profile) = self.profile_overrides(app_conf) {
+            profile.apply(app_conf);
+            app_conf.profile = self.profile.clone();
+        }
+        apply_field(&mut app_conf.dot_color, &self.dot_color);
+        apply_field(&mut app_conf.card_color, &self.card_color);
+        apply_field(&mut app_conf.font_color, &self.font_color);
+        apply_field(&mut app_conf.size, &self.size);
+        apply_field(&mut app_conf.x, &self.x);
+        apply_field(&mut app_conf.y, &self.y);
+        apply_field(&mut app_conf.lock, &self.lock);
+        apply_field(&mut app_conf.window_level, &self.window_level);
+        apply_field(&mut app_conf.mode, &self.mode);
+        apply_field(&mut app_conf.hide_leading_zeros, &self.hide_leading_zeros);
+        apply_field(&mut app_conf.position_preset, &self.position_preset.map(Some));
+    }
+
+    /// 把仍然等于覆盖值的配置项还原成 `base` 中的值，运行中被修改过的配置项保持不变
+    pub fn unapply(&self, app_conf: &mut AppConfig, base: &AppConfig) {
+        if let Some(profile) = self.profile_overrides(base) {
+            profile.unapply(app_conf, base);
+        }
+        unapply_field(&mut app_conf.dot_color, &self.dot_color, &base.dot_color);
+        unapply_field(&mut app_conf.card_color, &self.card_color, &base.card_color);
+        unapply_field(&mut app_conf.font_color, &self.font_color, &base.font_color);
+        unapply_field(&mut app_conf.size, &self.size, &base.size);
+        unapply_field(&mut app_conf.x, &self.x, &base.x);
+        unapply_field(&mut app_conf.y, &self.y, &base.y);
+        unapply_field(&mut app_conf.lock, &self.lock, &base.lock);
+        unapply_field(&mut app_conf.window_level, &self.window_level, &base.window_level);
+        unapply_field(&mut app_conf.mode, &self.mode, &base.mode);
+        unapply_field(
+            &mut app_conf.hide_leading_zeros,
+            &self.hide_leading_zeros,
+            &base.hide_leading_zeros,
+        );
+        unapply_field(
+            &mut app_conf.position_preset,
+            &self.position_preset.map(Some),
+            &base.position_preset,
+        );
+        unapply_field(&mut app_conf.profile, &self.profile.clone().map(Some), &base.profile);
+    }
+
+    fn profile_overrides(&self, app_conf: &AppConfig) -> Option<ConfigOverrides> {
+        let profile = app_conf.profiles.get(self.profile.as_ref()?)?;
+        Some(profile.to_overrides())
+    }
+}
+
+fn merge_field<T>(field: &mut Option<T>, value: Option<T>) {
+    if value.is_some() {
+        *field = value;
+    }
+}
+
+fn remove_field<T>(field: &mut Option<T>, value: &Option<T>) {
+    if value.is_some() {
+        *field = None;
+    }
+}
+
+fn apply_field<T: Clone>(field: &mut T, value: &Option<T>) {
+    if let Some(value) = value {
+        *field = value.clone();
+    }
+}
+
+fn unapply_field<T: Clone + PartialEq>(field: &mut T, value: &Option<T>, base: &T) {
+    if value.as_ref() == Some(&*field) {
+        *field = base.clone();
+    }
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use super::{AppConfig, ConfigOverrides};
+
+/// 一组可以整体切换的外观和窗口配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Profile {
+    pub dot_color: String,
+    pub card_color: String,
+    pub font_color: String,
+    pub size: f64,
+    pub x: i32,
+    pub y: i32,
+    pub window_level: u32,
+}
+
+impl Profile {
+    pub fn from_config(app_conf: &AppConfig) -> Self {
+        Self {
+            dot_color: app_conf.dot_color.clone(),
+            card_color: app_conf.card_color.clone(),
+            font_color: app_conf.font_color.clone(),
+            size: app_conf.size,
+            x: app_conf.x,
+            y: app_conf.y,
+            window_level: app_conf.window_level,
+        }
+    }
+
+    pub fn to_overrides(&self) -> ConfigOverrides {
+        ConfigOverrides {
+            dot_color: Some(self.dot_color.clone()),
+            card_color: Some(self.card_color.clone()),
+            font_color: Some(self.font_color.clone()),
+            size: Some(self.size),
+            x: Some(self.x),
+            y: Some(self.y),
+            window_level: Some(self.window_level),
+            ..Default::default()
+        }
+    }
+}
+
+impl AppConfig {
+    /// 按名称顺序排在当前配置方案之后的配置方案，最后一个之后回到第一个
+    pub fn next_profile(&self) -> Option<String> {
+        let current = self.profile.as_ref();
+        self.profiles
+            .keys()
+            .find(|name| current.is_some_and(|current| *name > current))
+            .or_else(|| self.profiles.keys().next())
+            .cloned()
+    }
+
+    /// 切换到另一个配置方案，当前的取值会先保存到当前的配置方案中
+    pub fn switch_profile(&mut self, name: &str) -> bool {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            return false;
+        };
+
+        if let Some(current) = self.profile.clone() {
+            self.profiles.insert(current, Profile::from_config(self));
+        }
+        profile.to_overrides().apply(self);
+        self.profile = Some(name.to_owned());
+        true
+    }
+}
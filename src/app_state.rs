@@ -3,18 +3,53 @@ use std::time::Duration;
 use freya::prelude::*;
 use tokio::time::sleep;
 
-use crate::app_config::AppConfig;
+use crate::app_config::{to_window_level, AppConfig, ConfigError, ConfigOverrides};
 
 #[derive(Debug, Clone, Default)]
 pub struct AppState {
+    // 合并了覆盖项之后实际生效的配置
     pub app_conf: AppConfig,
+    // 配置文件中的配置
+    pub file_conf: AppConfig,
+    pub overrides: ConfigOverrides,
 }
 
 impl AppState {
-    pub fn new() -> Self {
-        Self {
-            app_conf: AppConfig::load(),
-        }
+    pub fn new(overrides: ConfigOverrides) -> Result<Self, ConfigError> {
+        let file_conf = AppConfig::load()?;
+        let mut app_conf = file_conf.clone();
+        overrides.apply(&mut app_conf);
+        app_conf.log_sources(&overrides);
+        Ok(Self {
+            app_conf,
+            file_conf,
+            overrides,
+        })
+    }
+
+    /// 窗口大小来自命令行或者环境变量，这时它总是逻辑像素
+    pub fn is_size_overridden(&self) -> bool {
+        self.overrides.size.is_some() || AppConfig::is_from_env("size")
+    }
+
+    /// 预设位置来自命令行或者环境变量，这时边距总是逻辑像素
+    pub fn is_preset_overridden(&self) -> bool {
+        self.overrides.position_preset.is_some() || AppConfig::is_from_env("position_preset")
+    }
+
+    /// 把覆盖项写入配置文件，之后它们不再是临时的
+    pub fn persist_overrides(&mut self) -> Result<(), ConfigError> {
+        // 指定了 --save 的配置项即使和环境变量相同也写入文件
+        let mut file_keys = AppConfig::file_keys();
+        file_keys.extend(self.overrides.keys().into_iter().map(str::to_owned));
+        std::fs::write(
+            AppConfig::get_conf_path(),
+            self.app_conf
+                .to_file_string(AppConfig::get_conf_format(), &file_keys)?,
+        )?;
+        self.file_conf = self.app_conf.clone();
+        self.overrides = ConfigOverrides::default();
+        Ok(())
     }
 }
 
@@ -22,22 +57,80 @@ pub fn use_app_state() -> AppState {
     consume_context::<AppState>()
 }
 
-pub fn use_app_conf() -> Signal<AppConfig> {
+/// 界面使用的配置和本次运行中的覆盖项，配置变化之后去掉覆盖项写入配置文件
+pub fn use_app_conf() -> (Signal<AppConfig>, Signal<ConfigOverrides>) {
     let app_state = use_app_state();
-    let app_conf = use_signal(|| app_state.app_conf);
+    let mut app_conf = use_signal(|| app_state.app_conf);
+    // 记录磁盘上配置文件当前的内容，用来区分是自己保存的还是外部修改的
+    let mut synced_conf = use_signal(|| app_state.file_conf);
+    let overrides = use_signal(|| app_state.overrides);
     let mut task: Signal<Option<Task>> = use_signal(|| None);
+    let platform = use_platform();
 
     use_effect(use_reactive(&app_conf.read().clone(), move |app_conf| {
         if let Some(task) = task.write().take() {
             task.cancel();
         }
+        // 命令行覆盖的配置项不写入文件
+        let mut file_conf = app_conf;
+        // 旧版本的配置中 size 是物理像素，按照同样的显示器换算之后再比较
+        let mut base = synced_conf.peek().clone();
+        if let (None, Some(monitor)) = (&base.monitor, &file_conf.monitor) {
+            base.convert_to_logical_size(monitor.clone(), false, false);
+        }
+        overrides.peek().unapply(&mut file_conf, &base);
+        if *synced_conf.peek() == file_conf {
+            return;
+        }
         let move_task = Some(spawn(async move {
             sleep(Duration::from_millis(1500)).await;
-            app_conf.save().await;
+            file_conf.save().await;
+            synced_conf.set(file_conf);
             task.write().take();
         }));
         task.replace(move_task);
     }));
 
-    app_conf
+    use_effect(move || {
+        spawn(async move {
+            let mut last_modified = AppConfig::modified_time().await;
+            loop {
+                sleep(Duration::from_millis(1000)).await;
+                let modified = AppConfig::modified_time().await;
+                if modified == last_modified {
+                    continue;
+                }
+                last_modified = modified;
+
+                let Some(new_file_conf) = AppConfig::read().await else {
+                    continue;
+                };
+                // 自己保存的文件，内容和当前同步的一致，不需要重新加载
+                if *synced_conf.peek() == new_file_conf {
+                    continue;
+                }
+
+                let mut new_conf = new_file_conf.clone();
+                overrides.peek().apply(&mut new_conf);
+                let old_conf = app_conf.peek().clone();
+                if old_conf.size != new_conf.size
+                    || old_conf.x != new_conf.x
+                    || old_conf.y != new_conf.y
+                {
+                    platform.set_window_size_and_position(
+                        new_conf.window_size(platform.info().window_scale_factor),
+                        Point2D::new(new_conf.x as f32, new_conf.y as f32),
+                    );
+                }
+                if old_conf.window_level != new_conf.window_level {
+                    platform.set_window_level(to_window_level(new_conf.window_level));
+                }
+
+                synced_conf.set(new_file_conf);
+                app_conf.set(new_conf);
+            }
+        });
+    });
+
+    (app_conf, overrides)
 }
@@ -0,0 +1,102 @@
+use std::{collections::HashMap, io::Cursor, path::Path};
+
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+
+// 内置的声音，`sound_dir` 中同名的 .wav 或 .ogg 文件可以替换它们
+const SOUND_NAMES: [&str; 9] = [
+    "beep",
+    "click",
+    "cuckoo",
+    "bong",
+    "westminster_1",
+    "westminster_2",
+    "westminster_3",
+    "westminster_4",
+    "westminster_5",
+];
+
+/// 从 `sound_dir` 中读取的声音文件，按声音的名称记录
+pub type UserSounds = HashMap<String, Vec<u8>>;
+
+/// 播放内置的或者用户提供的声音，界面中通过 `use_audio` 共用一个
+pub struct AudioPlayer {
+    // 输出流被释放之后就不能再播放声音
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+}
+
+impl AudioPlayer {
+    /// 打开默认的音频设备，没有可用的设备时返回 None
+    pub fn new() -> Option<Self> {
+        let (stream, handle) = OutputStream::try_default()
+            .map_err(|e| log::warn!("failed to open audio output: {e}"))
+            .ok()?;
+        Some(Self {
+            _stream: stream,
+            handle,
+        })
+    }
+
+    /// 在后台依次播放多个声音，`user_sounds` 中的同名声音优先于内置的声音
+    pub fn play(&self, names: &[String], volume: f32, user_sounds: &UserSounds) {
+        let sink = match Sink::try_new(&self.handle) {
+            Ok(sink) => sink,
+            Err(e) => {
+                log::warn!("failed to play sound: {e}");
+                return;
+            }
+        };
+        sink.set_volume(volume);
+        for name in names {
+            let Some(data) = sound_data(name, user_sounds) else {
+                log::warn!("sound {name} not found");
+                continue;
+            };
+            match Decoder::new(Cursor::new(data)) {
+                Ok(source) => sink.append(source),
+                Err(e) => log::warn!("failed to decode sound {name}: {e}"),
+            }
+        }
+        sink.detach();
+    }
+}
+
+/// 在后台读取 `sound_dir` 中替换内置声音的文件，播放时不再读取磁盘
+pub async fn load_user_sounds(sound_dir: Option<String>) -> UserSounds {
+    let mut sounds = UserSounds::new();
+    let Some(sound_dir) = sound_dir else {
+        return sounds;
+    };
+    for name in SOUND_NAMES {
+        for extension in ["wav", "ogg"] {
+            let path = Path::new(&sound_dir).join(format!("{name}.{extension}"));
+            if let Ok(data) = tokio::fs::read(path).await {
+                sounds.insert(name.to_string(), data);
+                break;
+            }
+        }
+    }
+    sounds
+}
+
+fn sound_data(name: &str, user_sounds: &UserSounds) -> Option<Vec<u8>> {
+    user_sounds
+        .get(name)
+        .cloned()
+        .or_else(|| bundled_sound(name).map(<[u8]>::to_vec))
+}
+
+fn bundled_sound(name: &str) -> Option<&'static [u8]> {
+    Some(match name {
+        "beep" => include_bytes!("../assets/sounds/beep.wav"),
+        "click" => include_bytes!("../assets/sounds/click.wav"),
+        "cuckoo" => include_bytes!("../assets/sounds/cuckoo.wav"),
+        "bong" => include_bytes!("../assets/sounds/bong.wav"),
+        "westminster_1" => include_bytes!("../assets/sounds/westminster_1.wav"),
+        "westminster_2" => include_bytes!("../assets/sounds/westminster_2.wav"),
+        "westminster_3" => include_bytes!("../assets/sounds/westminster_3.wav"),
+        "westminster_4" => include_bytes!("../assets/sounds/westminster_4.wav"),
+        "westminster_5" => include_bytes!("../assets/sounds/westminster_5.wav"),
+        _ => return None,
+    })
+}
@@ -0,0 +1,250 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+
+use super::{windows_zones, CalendarEvent, EventZone, RecurrenceRule};
+
+/// ICS 中的一行内容，格式为 `NAME;PARAM=VALUE:VALUE`
+#[derive(Debug, Clone, PartialEq)]
+struct ContentLine {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl ContentLine {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    // 带 TZID 的时间使用对应的时区，以 Z 结尾的是 UTC 时间，其他的是本地时间
+    fn zone(&self, time: &IcsTime) -> EventZone {
+        if time.utc {
+            return EventZone::Utc;
+        }
+        match self.param("TZID") {
+            Some(tzid) if !time.date_only => parse_tzid(tzid),
+            _ => EventZone::Local,
+        }
+    }
+
+    // EXDATE 和 RDATE 可以用逗号分隔多个时间，只有日期时使用事件开始的钟点
+    fn times(&self, start: NaiveDateTime) -> Vec<DateTime<Utc>> {
+        self.value
+            .split(',')
+            .filter_map(parse_time)
+            .map(|time| {
+                let zone = self.zone(&time);
+                let naive = if time.date_only {
+                    time.time.date().and_time(start.time())
+                } else {
+                    time.time
+                };
+                zone.to_utc(naive)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IcsTime {
+    pub time: NaiveDateTime,
+    pub utc: bool,
+    // 全天事件只有日期
+    pub date_only: bool,
+}
+
+/// 解析 `20240101`、`20240101T090000` 和 `20240101T090000Z` 格式的时间
+pub fn parse_time(value: &str) -> Option<IcsTime> {
+    let value = value.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+        return Some(IcsTime {
+            time: date.and_hms_opt(0, 0, 0)?,
+            utc: false,
+            date_only: true,
+        });
+    }
+    let (value, utc) = match value.strip_suffix('Z') {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some(IcsTime {
+        time,
+        utc,
+        date_only: false,
+    })
+}
+
+// 有些日历导出的 TZID 带有前缀，例如 `/mozilla.org/20050126_1/Europe/Berlin`，
+// Windows 上的日历使用 Windows 时区名称
+fn parse_tzid(tzid: &str) -> EventZone {
+    let tzid = tzid.trim_matches('"');
+    let candidates = std::iter::once(tzid)
+        .chain(tzid.match_indices('/').map(|(index, _)| &tzid[index + 1..]))
+        .chain(windows_zones::iana_name(tzid));
+    for candidate in candidates {
+        if let Ok(tz) = candidate.parse::<Tz>() {
+            return EventZone::Named(tz);
+        }
+    }
+    log::warn!("calendar: unknown time zone \"{tzid}\", using local time");
+    EventZone::Local
+}
+
+/// 解析 ICS 文件中的所有 VEVENT，无法识别的事件会被跳过
+pub fn parse_calendar(text: &str) -> Vec<CalendarEvent> {
+    let mut events = vec![];
+    let mut event = None as Option<Vec<ContentLine>>;
+    // VEVENT 里面嵌套的 VALARM 等组件
+    let mut nested = 0;
+
+    for line in unfold(text).iter().filter_map(|line| parse_line(line)) {
+        let is_event = line.value.eq_ignore_ascii_case("VEVENT");
+        match line.name.as_str() {
+            "BEGIN" if is_event => event = Some(vec![]),
+            "END" if is_event => {
+                if let Some(event) = event.take().and_then(|lines| build_event(&lines)) {
+                    events.push(event);
+                }
+                nested = 0;
+            }
+            "BEGIN" if event.is_some() => nested += 1,
+            "END" if event.is_some() => nested -= 1,
+            _ if nested == 0 => {
+                if let Some(event) = event.as_mut() {
+                    event.push(line);
+                }
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+fn build_event(lines: &[ContentLine]) -> Option<CalendarEvent> {
+    let find = |name: &str| lines.iter().find(|line| line.name == name);
+
+    let summary = find("SUMMARY")
+        .map(|line| unescape(&line.value))
+        .unwrap_or_default();
+    let uid = find("UID")
+        .map(|line| line.value.clone())
+        .unwrap_or_default();
+
+    let dtstart = find("DTSTART")?;
+    let Some(start) = parse_time(&dtstart.value) else {
+        log::warn!("calendar: {summary}: invalid DTSTART \"{}\"", dtstart.value);
+        return None;
+    };
+    let zone = dtstart.zone(&start);
+    let start = start.time;
+
+    let rule = find("RRULE").and_then(|line| match line.value.parse::<RecurrenceRule>() {
+        Ok(rule) => Some(rule),
+        Err(e) => {
+            log::warn!("calendar: {summary}: {e}, only the first occurrence is used");
+            None
+        }
+    });
+    let times = |name: &str| {
+        lines
+            .iter()
+            .filter(|line| line.name == name)
+            .flat_map(|line| line.times(start))
+            .collect::<Vec<_>>()
+    };
+    let recurrence_id = find("RECURRENCE-ID").and_then(|line| {
+        let time = parse_time(&line.value)?;
+        Some(line.zone(&time).to_utc(time.time))
+    });
+    let cancelled = find("STATUS").is_some_and(|line| line.value.eq_ignore_ascii_case("CANCELLED"));
+
+    Some(CalendarEvent {
+        uid,
+        summary,
+        start,
+        zone,
+        rule,
+        // 只支持具体的时间，不支持 VALUE=PERIOD
+        rdates: times("RDATE"),
+        exdates: times("EXDATE"),
+        recurrence_id,
+        cancelled,
+    })
+}
+
+// 以空格或制表符开头的行是上一行的延续
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn parse_line(line: &str) -> Option<ContentLine> {
+    // 参数的值可以用引号括起来，里面的冒号和分号不是分隔符
+    let mut in_quotes = false;
+    let mut split = None;
+    for (index, char) in line.char_indices() {
+        match char {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                split = Some(index);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let (head, value) = line.split_at(split?);
+
+    let mut parts = vec![];
+    let mut part = String::new();
+    in_quotes = false;
+    for char in head.chars() {
+        match char {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => parts.push(std::mem::take(&mut part)),
+            _ => part.push(char),
+        }
+    }
+    parts.push(part);
+
+    let mut parts = parts.into_iter();
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| {
+            let (name, value) = param.split_once('=')?;
+            Some((name.to_ascii_uppercase(), value.to_string()))
+        })
+        .collect();
+    Some(ContentLine {
+        name,
+        params,
+        value: value[1..].trim().to_string(),
+    })
+}
+
+// SUMMARY 中转义的逗号、分号、反斜杠和换行
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            result.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => result.push(' '),
+            Some(char) => result.push(char),
+            None => {}
+        }
+    }
+    result
+}
@@ -0,0 +1,369 @@
+mod ics;
+mod recurrence;
+mod windows_zones;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, LocalResult, NaiveDateTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+pub use ics::parse_calendar;
+pub use recurrence::RecurrenceRule;
+
+// 最多提前一天提醒
+const MAX_ALERT_MINUTES: u64 = 24 * 60;
+const MAX_LOOKAHEAD_DAYS: u32 = 366;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarConfig {
+    // 本地的 .ics 文件，文件变化后会重新读取
+    pub files: Vec<String>,
+    // 事件开始前多少分钟提醒，0 表示不提醒
+    pub alert_minutes: u64,
+    // 只显示这么多天以内的事件
+    pub lookahead_days: u32,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            files: vec![],
+            alert_minutes: 0,
+            lookahead_days: 7,
+        }
+    }
+}
+
+impl CalendarConfig {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = vec![];
+        if self.alert_minutes > MAX_ALERT_MINUTES {
+            errors.push(format!(
+                "calendar.alert_minutes: must be at most {MAX_ALERT_MINUTES}, got {}",
+                self.alert_minutes
+            ));
+        }
+        if !(1..=MAX_LOOKAHEAD_DAYS).contains(&self.lookahead_days) {
+            errors.push(format!(
+                "calendar.lookahead_days: must be between 1 and {MAX_LOOKAHEAD_DAYS}, got {}",
+                self.lookahead_days
+            ));
+        }
+        errors
+    }
+}
+
+/// 事件时间所在的时区
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventZone {
+    Utc,
+    // 没有指定时区的时间和全天事件按本地时间计算
+    Local,
+    Named(Tz),
+}
+
+impl EventZone {
+    pub fn to_utc(&self, time: NaiveDateTime) -> DateTime<Utc> {
+        match self {
+            EventZone::Utc => Utc.from_utc_datetime(&time),
+            EventZone::Local => resolve(&chrono::Local, time),
+            EventZone::Named(tz) => resolve(tz, time),
+        }
+    }
+}
+
+// 夏令时开始时不存在的时间顺延一小时，结束时重复的时间取较早的一个
+fn resolve<T: TimeZone>(tz: &T, time: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&time) {
+        LocalResult::Single(time) => time.with_timezone(&Utc),
+        LocalResult::Ambiguous(earliest, _) => earliest.with_timezone(&Utc),
+        LocalResult::None => tz
+            .from_local_datetime(&(time + TimeDelta::hours(1)))
+            .earliest()
+            .map(|time| time.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&time)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    pub start: NaiveDateTime,
+    pub zone: EventZone,
+    pub rule: Option<RecurrenceRule>,
+    pub rdates: Vec<DateTime<Utc>>,
+    pub exdates: Vec<DateTime<Utc>>,
+    // 修改过的某一次重复，替换掉原来那一次
+    pub recurrence_id: Option<DateTime<Utc>>,
+    pub cancelled: bool,
+}
+
+impl CalendarEvent {
+    /// `after` 之后、`horizon` 之前最近的一次开始时间，`skip` 中的时间不算
+    pub fn next_start(
+        &self,
+        after: DateTime<Utc>,
+        horizon: DateTime<Utc>,
+        skip: &[DateTime<Utc>],
+    ) -> Option<DateTime<Utc>> {
+        let is_wanted = |time: &DateTime<Utc>| {
+            *time > after && *time <= horizon && !self.exdates.contains(time) && !skip.contains(time)
+        };
+
+        let mut next = self.rdates.iter().copied().filter(is_wanted).min();
+        match &self.rule {
+            None => {
+                let start = self.zone.to_utc(self.start);
+                if is_wanted(&start) {
+                    next = Some(next.map_or(start, |next| next.min(start)));
+                }
+            }
+            Some(rule) => rule.expand(self.start, self.zone, horizon, |time| {
+                // 重复的时间按先后顺序产生，找到第一个就可以停下
+                if !is_wanted(&time) {
+                    return time <= horizon;
+                }
+                next = Some(next.map_or(time, |next| next.min(time)));
+                false
+            }),
+        }
+        next
+    }
+}
+
+/// 日历中接下来的一个事件
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpcomingEvent {
+    pub uid: String,
+    pub title: String,
+    pub start: DateTime<Utc>,
+}
+
+pub fn next_event(
+    events: &[CalendarEvent],
+    after: DateTime<Utc>,
+    horizon: DateTime<Utc>,
+) -> Option<UpcomingEvent> {
+    let mut overridden = HashMap::<&str, Vec<DateTime<Utc>>>::new();
+    for event in events {
+        if let Some(recurrence_id) = event.recurrence_id {
+            overridden
+                .entry(event.uid.as_str())
+                .or_default()
+                .push(recurrence_id);
+        }
+    }
+
+    events
+        .iter()
+        .filter(|event| !event.cancelled)
+        .filter_map(|event| {
+            let skip = match event.recurrence_id {
+                None => overridden.get(event.uid.as_str()).map(Vec::as_slice),
+                Some(_) => None,
+            };
+            let start = event.next_start(after, horizon, skip.unwrap_or_default())?;
+            Some(UpcomingEvent {
+                uid: event.uid.clone(),
+                title: event.summary.clone(),
+                start,
+            })
+        })
+        .min_by_key(|event| event.start)
+}
+
+/// 距离事件开始的倒计时，一天以上显示天数
+pub fn format_countdown(remaining: TimeDelta) -> String {
+    let seconds = remaining.num_seconds().max(0);
+    let (days, hours, minutes, seconds) = (
+        seconds / 86400,
+        seconds / 3600 % 24,
+        seconds / 60 % 60,
+        seconds % 60,
+    );
+    if days > 0 {
+        format!("{days}d {hours:02}:{minutes:02}")
+    } else {
+        format!("{hours:02}:{minutes:02}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use super::*;
+
+    // 把几个 VEVENT 包装成一个日历，事件自己的 SUMMARY 在前面，优先于默认的标题
+    fn calendar(events: &[&str]) -> Vec<CalendarEvent> {
+        let events = events
+            .iter()
+            .map(|lines| format!("BEGIN:VEVENT\nUID:test\n{lines}\nSUMMARY:Test\nEND:VEVENT\n"))
+            .collect::<String>();
+        parse_calendar(&format!("BEGIN:VCALENDAR\n{events}END:VCALENDAR\n"))
+    }
+
+    fn utc(time: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M")
+            .unwrap()
+            .and_utc()
+    }
+
+    // 从 `after` 开始依次找出接下来最多 `max` 次开始时间，用 UTC 表示
+    fn starts(events: &[CalendarEvent], after: &str, max: usize) -> Vec<String> {
+        let mut after = utc(after);
+        let horizon = after + TimeDelta::days(MAX_LOOKAHEAD_DAYS as i64);
+        let mut starts = vec![];
+        while starts.len() < max {
+            let Some(event) = next_event(events, after, horizon) else {
+                break;
+            };
+            starts.push(event.start.format("%Y-%m-%d %H:%M").to_string());
+            after = event.start;
+        }
+        starts
+    }
+
+    #[test]
+    fn weekly_keeps_wall_time_across_dst() {
+        // 纽约 2024-03-10 开始夏令时，2024-11-03 结束
+        let events =
+            calendar(&["DTSTART;TZID=America/New_York:20240303T090000\nRRULE:FREQ=WEEKLY"]);
+        assert_eq!(
+            starts(&events, "2024-03-01 00:00", 3),
+            ["2024-03-03 14:00", "2024-03-10 13:00", "2024-03-17 13:00"]
+        );
+        assert_eq!(
+            starts(&events, "2024-10-26 00:00", 2),
+            ["2024-10-27 13:00", "2024-11-03 14:00"]
+        );
+    }
+
+    #[test]
+    fn monthly_last_friday() {
+        let events = calendar(&["DTSTART:20240126T100000Z\nRRULE:FREQ=MONTHLY;BYDAY=-1FR"]);
+        assert_eq!(
+            starts(&events, "2024-01-01 00:00", 5),
+            [
+                "2024-01-26 10:00",
+                "2024-02-23 10:00",
+                "2024-03-29 10:00",
+                "2024-04-26 10:00",
+                "2024-05-31 10:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn month_day_31_skips_short_months() {
+        let events = calendar(&["DTSTART:20240131T080000Z\nRRULE:FREQ=MONTHLY;BYMONTHDAY=31"]);
+        assert_eq!(
+            starts(&events, "2024-01-01 00:00", 5),
+            [
+                "2024-01-31 08:00",
+                "2024-03-31 08:00",
+                "2024-05-31 08:00",
+                "2024-07-31 08:00",
+                "2024-08-31 08:00",
+            ]
+        );
+    }
+
+    #[test]
+    fn count_includes_excluded_dates() {
+        // EXDATE 去掉的那几次也算在 COUNT 里
+        let events = calendar(&[
+            "DTSTART:20240101T090000Z\nRRULE:FREQ=DAILY;COUNT=5\nEXDATE:20240102T090000Z,20240104T090000Z",
+        ]);
+        assert_eq!(
+            starts(&events, "2023-12-31 00:00", 10),
+            ["2024-01-01 09:00", "2024-01-03 09:00", "2024-01-05 09:00"]
+        );
+    }
+
+    #[test]
+    fn until_in_utc_and_local_time() {
+        // 本地时间的 UNTIL 和 DTSTART 在同一个时区，包括 1 月 10 日 9 点这一次
+        let events = calendar(&[
+            "DTSTART;TZID=America/New_York:20240108T090000\nRRULE:FREQ=DAILY;UNTIL=20240110T090000",
+        ]);
+        assert_eq!(
+            starts(&events, "2024-01-01 00:00", 10),
+            ["2024-01-08 14:00", "2024-01-09 14:00", "2024-01-10 14:00"]
+        );
+
+        // UTC 的 13 点是纽约的 8 点，1 月 10 日那一次不包括在内
+        let events = calendar(&[
+            "DTSTART;TZID=America/New_York:20240108T090000\nRRULE:FREQ=DAILY;UNTIL=20240110T130000Z",
+        ]);
+        assert_eq!(
+            starts(&events, "2024-01-01 00:00", 10),
+            ["2024-01-08 14:00", "2024-01-09 14:00"]
+        );
+    }
+
+    #[test]
+    fn recurrence_id_overrides_and_cancels() {
+        let events = calendar(&[
+            "DTSTART:20240101T090000Z\nRRULE:FREQ=DAILY;COUNT=5",
+            "RECURRENCE-ID:20240102T090000Z\nDTSTART:20240102T150000Z\nSUMMARY:Moved",
+            "RECURRENCE-ID:20240103T090000Z\nDTSTART:20240103T090000Z\nSTATUS:CANCELLED",
+        ]);
+        assert_eq!(
+            starts(&events, "2023-12-31 00:00", 10),
+            [
+                "2024-01-01 09:00",
+                "2024-01-02 15:00",
+                "2024-01-04 09:00",
+                "2024-01-05 09:00",
+            ]
+        );
+        let moved = next_event(&events, utc("2024-01-02 00:00"), utc("2024-01-03 00:00"));
+        assert_eq!(moved.unwrap().title, "Moved");
+    }
+
+    #[test]
+    fn yearly_by_day_without_month_is_every_week() {
+        let events = calendar(&["DTSTART:20240101T090000Z\nRRULE:FREQ=YEARLY;BYDAY=MO"]);
+        assert_eq!(
+            starts(&events, "2023-12-31 00:00", 3),
+            ["2024-01-01 09:00", "2024-01-08 09:00", "2024-01-15 09:00"]
+        );
+        assert_eq!(
+            starts(&events, "2024-12-25 00:00", 2),
+            ["2024-12-30 09:00", "2025-01-06 09:00"]
+        );
+    }
+
+    #[test]
+    fn yearly_by_month_day_without_month_is_every_month() {
+        let events = calendar(&["DTSTART:20240101T090000Z\nRRULE:FREQ=YEARLY;BYMONTHDAY=1"]);
+        assert_eq!(
+            starts(&events, "2023-12-31 00:00", 3),
+            ["2024-01-01 09:00", "2024-02-01 09:00", "2024-03-01 09:00"]
+        );
+
+        // 只有开始日期时仍然每年一次
+        let events = calendar(&["DTSTART:20240315T090000Z\nRRULE:FREQ=YEARLY"]);
+        assert_eq!(starts(&events, "2024-01-01 00:00", 1), ["2024-03-15 09:00"]);
+        assert_eq!(starts(&events, "2024-03-16 00:00", 1), ["2025-03-15 09:00"]);
+    }
+
+    #[test]
+    fn windows_zone_names_are_mapped() {
+        // Outlook 导出的 TZID 使用 Windows 时区名称，柏林冬天是 UTC+1，夏天是 UTC+2
+        let events = calendar(&[
+            "DTSTART;TZID=W. Europe Standard Time:20240115T090000\nRRULE:FREQ=YEARLY;BYMONTH=1,7",
+        ]);
+        assert_eq!(
+            starts(&events, "2024-01-01 00:00", 2),
+            ["2024-01-15 08:00", "2024-07-15 07:00"]
+        );
+
+        let events = calendar(&["DTSTART;TZID=\"Eastern Standard Time\":20240115T090000"]);
+        assert_eq!(starts(&events, "2024-01-01 00:00", 1), ["2024-01-15 14:00"]);
+    }
+}
@@ -0,0 +1,349 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveDateTime, Utc, Weekday};
+
+use super::{ics::parse_time, EventZone};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// RRULE 的截止时间，不是 UTC 时间时和 DTSTART 在同一个时区
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Until {
+    time: NaiveDateTime,
+    utc: bool,
+}
+
+/// 日历事件的重复规则，支持按天、周、月、年重复
+///
+/// 支持 INTERVAL、COUNT、UNTIL、BYDAY、BYMONTHDAY、BYMONTH 和 WKST，
+/// 其他的 BYxxx 规则无法正确展开，解析时返回错误。
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<Until>,
+    // 星期几，带序号时表示一个月或一年中的第几个，负数从后往前数
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+    week_start: Weekday,
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let mut frequency = None;
+        let mut result = RecurrenceRule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: vec![],
+            by_month_day: vec![],
+            by_month: vec![],
+            week_start: Weekday::Mon,
+        };
+
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let Some((key, value)) = part.split_once('=') else {
+                return Err(format!("invalid rule part \"{part}\""));
+            };
+            let invalid = || format!("invalid {key} \"{value}\"");
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("unsupported FREQ \"{value}\"")),
+                    })
+                }
+                "INTERVAL" => {
+                    result.interval = value.parse().ok().filter(|&n| n > 0).ok_or_else(invalid)?
+                }
+                "COUNT" => result.count = Some(value.parse().map_err(|_| invalid())?),
+                "UNTIL" => {
+                    let time = parse_time(value).ok_or_else(invalid)?;
+                    // 只有日期时当天的事件也包括在内
+                    let until = if time.date_only {
+                        time.time.date().and_hms_opt(23, 59, 59).unwrap()
+                    } else {
+                        time.time
+                    };
+                    result.until = Some(Until {
+                        time: until,
+                        utc: time.utc,
+                    });
+                }
+                "BYDAY" => {
+                    result.by_day = value
+                        .split(',')
+                        .map(parse_week_day)
+                        .collect::<Option<_>>()
+                        .ok_or_else(invalid)?
+                }
+                "BYMONTHDAY" => {
+                    result.by_month_day = value
+                        .split(',')
+                        .map(|day| {
+                            day.parse()
+                                .ok()
+                                .filter(|day: &i32| (1..=31).contains(&day.abs()))
+                        })
+                        .collect::<Option<_>>()
+                        .ok_or_else(invalid)?
+                }
+                "BYMONTH" => {
+                    result.by_month = value
+                        .split(',')
+                        .map(|month| month.parse().ok().filter(|month| (1..=12).contains(month)))
+                        .collect::<Option<_>>()
+                        .ok_or_else(invalid)?
+                }
+                "WKST" => result.week_start = parse_weekday(value).ok_or_else(invalid)?,
+                _ => return Err(format!("unsupported rule part \"{part}\"")),
+            }
+        }
+
+        result.frequency = frequency.ok_or("missing FREQ")?;
+        Ok(result)
+    }
+}
+
+impl RecurrenceRule {
+    /// 按时间顺序依次把每次重复交给 `visit`，`visit` 返回 false 时停止
+    ///
+    /// 重复在 `zone` 的本地时间中计算，所以跨过夏令时之后事件仍在同一个钟点。
+    /// 超过 `horizon` 之后不再继续，避免没有结束时间的规则一直算下去。
+    pub fn expand(
+        &self,
+        start: NaiveDateTime,
+        zone: EventZone,
+        horizon: DateTime<Utc>,
+        mut visit: impl FnMut(DateTime<Utc>) -> bool,
+    ) {
+        let until = self.until.map(|until| {
+            if until.utc {
+                EventZone::Utc.to_utc(until.time)
+            } else {
+                zone.to_utc(until.time)
+            }
+        });
+        // 时区的差别不会超过一天
+        let last_date = horizon.date_naive() + Days::new(1);
+
+        let mut count = 0;
+        for period in 0.. {
+            let Some((first_date, dates)) = self.period_dates(start.date(), period) else {
+                return;
+            };
+            if first_date > last_date {
+                return;
+            }
+            for date in dates {
+                let time = date.and_time(start.time());
+                if time < start {
+                    continue;
+                }
+                if self.count.is_some_and(|max| count >= max) {
+                    return;
+                }
+                let time = zone.to_utc(time);
+                if until.is_some_and(|until| time > until) {
+                    return;
+                }
+                count += 1;
+                if !visit(time) {
+                    return;
+                }
+            }
+        }
+    }
+
+    // 第 `period` 个周期的第一天，以及这个周期内按顺序排列的所有日期
+    fn period_dates(&self, start: NaiveDate, period: u32) -> Option<(NaiveDate, Vec<NaiveDate>)> {
+        let step = period.checked_mul(self.interval)?;
+        match self.frequency {
+            Frequency::Daily => {
+                let date = start.checked_add_days(Days::new(step as u64))?;
+                let matched = self.matches_month(date)
+                    && self.matches_month_day(date)
+                    && (self.by_day.is_empty()
+                        || self
+                            .by_day
+                            .iter()
+                            .any(|(_, weekday)| *weekday == date.weekday()));
+                Some((date, if matched { vec![date] } else { vec![] }))
+            }
+            Frequency::Weekly => {
+                let offset = days_since(start.weekday(), self.week_start);
+                let first = start
+                    .checked_sub_days(Days::new(offset as u64))?
+                    .checked_add_days(Days::new(step as u64 * 7))?;
+                let mut dates = if self.by_day.is_empty() {
+                    vec![start.weekday()]
+                } else {
+                    self.by_day.iter().map(|(_, weekday)| *weekday).collect()
+                }
+                .into_iter()
+                .filter_map(|weekday| {
+                    first.checked_add_days(Days::new(days_since(weekday, self.week_start) as u64))
+                })
+                .filter(|date| self.matches_month(*date))
+                .collect::<Vec<_>>();
+                dates.sort();
+                dates.dedup();
+                Some((first, dates))
+            }
+            Frequency::Monthly => {
+                let first = start.with_day(1)?.checked_add_months(Months::new(step))?;
+                let dates = if self.matches_month(first) {
+                    self.month_dates(first, start.day())
+                } else {
+                    vec![]
+                };
+                Some((first, dates))
+            }
+            Frequency::Yearly => {
+                let year = start.year().checked_add(step.try_into().ok()?)?;
+                let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                // 只有 BYDAY 时展开到一年中的每一周，序号按一年中的第几个计算
+                if self.by_month.is_empty()
+                    && self.by_month_day.is_empty()
+                    && !self.by_day.is_empty()
+                {
+                    let dates = first
+                        .iter_days()
+                        .take_while(|date| date.year() == year)
+                        .filter(|date| self.matches_day_of_year(*date))
+                        .collect();
+                    return Some((first, dates));
+                }
+
+                // 没有 BYMONTH 时 BYMONTHDAY 展开到每个月，两个都没有时只在开始日期的那个月
+                let months = if !self.by_month.is_empty() {
+                    let mut months = self.by_month.clone();
+                    months.sort();
+                    months.dedup();
+                    months
+                } else if !self.by_month_day.is_empty() {
+                    (1..=12).collect()
+                } else {
+                    vec![start.month()]
+                };
+                let dates = months
+                    .into_iter()
+                    .filter_map(|month| NaiveDate::from_ymd_opt(year, month, 1))
+                    .flat_map(|month| self.month_dates(month, start.day()))
+                    .collect();
+                Some((first, dates))
+            }
+        }
+    }
+
+    // 某个月里符合规则的日期，没有 BYDAY 和 BYMONTHDAY 时使用开始日期的那一天
+    fn month_dates(&self, first: NaiveDate, start_day: u32) -> Vec<NaiveDate> {
+        if self.by_day.is_empty() && self.by_month_day.is_empty() {
+            // 没有这一天的月份跳过，例如每月 31 日
+            return first.with_day(start_day).into_iter().collect();
+        }
+        first
+            .iter_days()
+            .take_while(|date| date.month() == first.month())
+            .filter(|date| self.matches_month_day(*date) && self.matches_day_of_month(*date))
+            .collect()
+    }
+
+    fn matches_month(&self, date: NaiveDate) -> bool {
+        self.by_month.is_empty() || self.by_month.contains(&date.month())
+    }
+
+    fn matches_month_day(&self, date: NaiveDate) -> bool {
+        if self.by_month_day.is_empty() {
+            return true;
+        }
+        let days = days_in_month(date) as i32;
+        let day = date.day() as i32;
+        self.by_month_day
+            .iter()
+            .any(|&by_day| by_day == day || by_day == day - days - 1)
+    }
+
+    fn matches_day_of_month(&self, date: NaiveDate) -> bool {
+        matches_weekday(&self.by_day, date, date.day(), days_in_month(date))
+    }
+
+    fn matches_day_of_year(&self, date: NaiveDate) -> bool {
+        let days = if date.leap_year() { 366 } else { 365 };
+        matches_weekday(&self.by_day, date, date.ordinal(), days)
+    }
+}
+
+// `day` 是日期在这个月或这一年中的第几天，`days` 是总天数
+fn matches_weekday(
+    by_day: &[(Option<i32>, Weekday)],
+    date: NaiveDate,
+    day: u32,
+    days: u32,
+) -> bool {
+    if by_day.is_empty() {
+        return true;
+    }
+    let from_start = ((day - 1) / 7 + 1) as i32;
+    let from_end = -(((days - day) / 7 + 1) as i32);
+    by_day.iter().any(|(ordinal, weekday)| {
+        *weekday == date.weekday()
+            && ordinal.map_or(true, |ordinal| ordinal == from_start || ordinal == from_end)
+    })
+}
+
+// 从 `week_start` 到 `weekday` 相隔的天数
+fn days_since(weekday: Weekday, week_start: Weekday) -> u32 {
+    (weekday.num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let first = date.with_day(1).unwrap();
+    let next = first + Months::new(1);
+    (next - first).num_days() as u32
+}
+
+// 例如 MO、2TU、-1FR
+fn parse_week_day(value: &str) -> Option<(Option<i32>, Weekday)> {
+    let value = value.trim();
+    let split = value.len().checked_sub(2)?;
+    let (ordinal, weekday) = (value.get(..split)?, value.get(split..)?);
+    let ordinal = if ordinal.is_empty() {
+        None
+    } else {
+        Some(
+            ordinal
+                .parse::<i32>()
+                .ok()
+                .filter(|n| *n != 0 && n.abs() <= 53)?,
+        )
+    };
+    Some((ordinal, parse_weekday(weekday)?))
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    match value.to_ascii_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
@@ -0,0 +1,371 @@
+use chrono::{Local, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChimeStyle {
+    #[default]
+    Off,
+    // 每次报时响一声
+    Beep,
+    // 整点按钟点数报时，刻钟响一声
+    Cuckoo,
+    // 威斯敏斯特钟声，整点之后按钟点数敲钟
+    Westminster,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChimeConfig {
+    pub style: ChimeStyle,
+    // 是否在一刻、半点和三刻报时
+    pub quarters: bool,
+    pub volume: f32,
+    // 免打扰时段，格式为 HH:MM，结束时间早于开始时间时跨过午夜
+    pub quiet_start: Option<String>,
+    pub quiet_end: Option<String>,
+    // 自定义声音所在的目录，同名的 wav 或 ogg 文件会替换内置的声音
+    pub sound_dir: Option<String>,
+}
+
+impl Default for ChimeConfig {
+    fn default() -> Self {
+        Self {
+            style: ChimeStyle::Off,
+            quarters: true,
+            volume: 0.8,
+            quiet_start: None,
+            quiet_end: None,
+            sound_dir: None,
+        }
+    }
+}
+
+impl ChimeConfig {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = vec![];
+        if !(0.0..=1.0).contains(&self.volume) {
+            errors.push(format!(
+                "chime.volume: must be between 0 and 1, got {}",
+                self.volume
+            ));
+        }
+        for (name, time) in [
+            ("quiet_start", &self.quiet_start),
+            ("quiet_end", &self.quiet_end),
+        ] {
+            if let Some(time) = time {
+                if parse_time(time).is_none() {
+                    errors.push(format!("chime.{name}: expected HH:MM, got \"{time}\""));
+                }
+            }
+        }
+        if self.quiet_start.is_some() != self.quiet_end.is_some() {
+            errors.push("chime: quiet_start and quiet_end must be set together".to_string());
+        }
+        errors
+    }
+
+    /// 是否在免打扰时段内，开始时间包含在内，结束时间不包含
+    pub fn is_quiet(&self, time: NaiveTime) -> bool {
+        let (Some(start), Some(end)) = (
+            self.quiet_start.as_deref().and_then(parse_time),
+            self.quiet_end.as_deref().and_then(parse_time),
+        ) else {
+            return false;
+        };
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChimeSound {
+    Beep,
+    Cuckoo,
+    // 威斯敏斯特钟声的五个乐句，从 1 开始
+    Westminster(u8),
+    Bong,
+}
+
+impl ChimeSound {
+    /// 声音文件的名称，不包括扩展名
+    pub fn name(&self) -> String {
+        match self {
+            ChimeSound::Beep => "beep".to_string(),
+            ChimeSound::Cuckoo => "cuckoo".to_string(),
+            ChimeSound::Westminster(phrase) => format!("westminster_{phrase}"),
+            ChimeSound::Bong => "bong".to_string(),
+        }
+    }
+}
+
+/// 某个时刻需要依次播放的声音，不是整点或刻钟时为空
+pub fn chime_sounds(
+    style: ChimeStyle,
+    quarters: bool,
+    hour: u32,
+    minute: u32,
+) -> Vec<ChimeSound> {
+    let quarter = match minute {
+        0 => 0,
+        15 => 1,
+        30 => 2,
+        45 => 3,
+        _ => return vec![],
+    };
+    if quarter != 0 && !quarters {
+        return vec![];
+    }
+    // 12 小时制的钟点数
+    let strikes = match hour % 12 {
+        0 => 12,
+        hour => hour as usize,
+    };
+
+    match style {
+        ChimeStyle::Off => vec![],
+        ChimeStyle::Beep => vec![ChimeSound::Beep],
+        ChimeStyle::Cuckoo if quarter == 0 => vec![ChimeSound::Cuckoo; strikes],
+        ChimeStyle::Cuckoo => vec![ChimeSound::Cuckoo],
+        ChimeStyle::Westminster => {
+            let phrases: &[u8] = match quarter {
+                1 => &[1],
+                2 => &[2, 3],
+                3 => &[4, 5, 1],
+                _ => &[2, 3, 4, 5],
+            };
+            let mut sounds = phrases
+                .iter()
+                .map(|phrase| ChimeSound::Westminster(*phrase))
+                .collect::<Vec<_>>();
+            if quarter == 0 {
+                sounds.extend(vec![ChimeSound::Bong; strikes]);
+            }
+            sounds
+        }
+    }
+}
+
+/// 报时使用的时间来源，可以替换成固定的时间
+pub trait TimeSource {
+    fn now(&self) -> NaiveTime;
+}
+
+pub struct LocalTime;
+
+impl TimeSource for LocalTime {
+    fn now(&self) -> NaiveTime {
+        Local::now().time()
+    }
+}
+
+/// 播放报时声音，`sounds` 按顺序连续播放
+pub trait AudioSink {
+    fn play(&mut self, sounds: &[ChimeSound], config: &ChimeConfig);
+}
+
+pub struct ChimeScheduler {
+    time_source: Box<dyn TimeSource>,
+    sink: Box<dyn AudioSink>,
+    // 上次检查时的小时和分钟
+    last_minute: Option<(u32, u32)>,
+}
+
+impl ChimeScheduler {
+    pub fn new(time_source: Box<dyn TimeSource>, sink: Box<dyn AudioSink>) -> Self {
+        Self {
+            time_source,
+            sink,
+            last_minute: None,
+        }
+    }
+
+    /// 时间变化时调用，每分钟最多报时一次，返回播放的声音
+    ///
+    /// 第一次调用只记录时间，避免在整点这一分钟内启动时马上报时
+    pub fn tick(&mut self, config: &ChimeConfig) -> Vec<ChimeSound> {
+        let now = self.time_source.now();
+        let minute = (now.hour(), now.minute());
+        if self.last_minute == Some(minute) {
+            return vec![];
+        }
+        let is_first = self.last_minute.is_none();
+        self.last_minute = Some(minute);
+        if is_first || config.is_quiet(now) {
+            return vec![];
+        }
+
+        let sounds = chime_sounds(config.style, config.quarters, now.hour(), now.minute());
+        if !sounds.is_empty() {
+            self.sink.play(&sounds, config);
+        }
+        sounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+    };
+
+    use super::*;
+
+    // 测试中手动拨动的时间
+    #[derive(Clone)]
+    struct FixedTime(Rc<Cell<NaiveTime>>);
+
+    impl TimeSource for FixedTime {
+        fn now(&self) -> NaiveTime {
+            self.0.get()
+        }
+    }
+
+    // 记录每次播放的声音
+    #[derive(Clone, Default)]
+    struct RecordingSink(Rc<RefCell<Vec<Vec<ChimeSound>>>>);
+
+    impl AudioSink for RecordingSink {
+        fn play(&mut self, sounds: &[ChimeSound], _config: &ChimeConfig) {
+            self.0.borrow_mut().push(sounds.to_vec());
+        }
+    }
+
+    struct Clock {
+        time: FixedTime,
+        sink: RecordingSink,
+        scheduler: ChimeScheduler,
+    }
+
+    impl Clock {
+        fn new(hour: u32, minute: u32) -> Self {
+            let time = FixedTime(Rc::new(Cell::new(at(hour, minute))));
+            let sink = RecordingSink::default();
+            let scheduler = ChimeScheduler::new(Box::new(time.clone()), Box::new(sink.clone()));
+            Self {
+                time,
+                sink,
+                scheduler,
+            }
+        }
+
+        fn tick_at(&mut self, hour: u32, minute: u32, config: &ChimeConfig) -> Vec<ChimeSound> {
+            self.time.0.set(at(hour, minute));
+            self.scheduler.tick(config)
+        }
+
+        fn played(&self) -> Vec<Vec<ChimeSound>> {
+            self.sink.0.borrow().clone()
+        }
+    }
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn config(style: ChimeStyle) -> ChimeConfig {
+        ChimeConfig {
+            style,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn first_tick_is_silent() {
+        let config = config(ChimeStyle::Beep);
+        let mut clock = Clock::new(12, 0);
+        assert_eq!(clock.scheduler.tick(&config), vec![]);
+        assert!(clock.played().is_empty());
+
+        assert_eq!(clock.tick_at(12, 15, &config), vec![ChimeSound::Beep]);
+        assert_eq!(clock.played(), vec![vec![ChimeSound::Beep]]);
+    }
+
+    #[test]
+    fn chimes_once_per_minute() {
+        let config = config(ChimeStyle::Beep);
+        let mut clock = Clock::new(12, 14);
+        clock.scheduler.tick(&config);
+        assert_eq!(clock.tick_at(12, 15, &config), vec![ChimeSound::Beep]);
+        assert_eq!(clock.tick_at(12, 15, &config), vec![]);
+        assert_eq!(clock.tick_at(12, 16, &config), vec![]);
+        assert_eq!(clock.played().len(), 1);
+    }
+
+    #[test]
+    fn quarters_can_be_turned_off() {
+        let mut config = config(ChimeStyle::Cuckoo);
+        config.quarters = false;
+        let mut clock = Clock::new(2, 59);
+        clock.scheduler.tick(&config);
+        assert_eq!(clock.tick_at(3, 0, &config), vec![ChimeSound::Cuckoo; 3]);
+        assert_eq!(clock.tick_at(3, 15, &config), vec![]);
+        assert_eq!(clock.tick_at(3, 30, &config), vec![]);
+        assert_eq!(clock.tick_at(3, 45, &config), vec![]);
+
+        config.quarters = true;
+        assert_eq!(clock.tick_at(4, 15, &config), vec![ChimeSound::Cuckoo]);
+        assert_eq!(clock.tick_at(4, 20, &config), vec![]);
+        assert_eq!(clock.played().len(), 2);
+    }
+
+    #[test]
+    fn westminster_strikes_the_hour() {
+        use ChimeSound::{Bong, Westminster};
+
+        let config = config(ChimeStyle::Westminster);
+        let mut clock = Clock::new(14, 59);
+        clock.scheduler.tick(&config);
+
+        let mut expected = vec![
+            Westminster(2),
+            Westminster(3),
+            Westminster(4),
+            Westminster(5),
+        ];
+        expected.extend([Bong; 3]);
+        assert_eq!(clock.tick_at(15, 0, &config), expected);
+        assert_eq!(clock.tick_at(15, 15, &config), vec![Westminster(1)]);
+        assert_eq!(
+            clock.tick_at(15, 30, &config),
+            vec![Westminster(2), Westminster(3)]
+        );
+        assert_eq!(
+            clock.tick_at(15, 45, &config),
+            vec![Westminster(4), Westminster(5), Westminster(1)]
+        );
+
+        // 午夜敲 12 下
+        let midnight = clock.tick_at(0, 0, &config);
+        assert_eq!(midnight.iter().filter(|sound| **sound == Bong).count(), 12);
+    }
+
+    #[test]
+    fn quiet_hours_wrap_midnight() {
+        let mut config = config(ChimeStyle::Beep);
+        config.quiet_start = Some("22:00".to_string());
+        config.quiet_end = Some("07:00".to_string());
+        let mut clock = Clock::new(21, 44);
+        clock.scheduler.tick(&config);
+
+        assert_eq!(clock.tick_at(21, 45, &config), vec![ChimeSound::Beep]);
+        for (hour, minute) in [(22, 0), (23, 30), (0, 0), (3, 15), (6, 45)] {
+            assert_eq!(
+                clock.tick_at(hour, minute, &config),
+                vec![],
+                "{hour}:{minute}"
+            );
+        }
+        assert_eq!(clock.tick_at(7, 0, &config), vec![ChimeSound::Beep]);
+        assert_eq!(clock.played().len(), 2);
+    }
+}
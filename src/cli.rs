@@ -0,0 +1,272 @@
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::{
+    app_config::{AppConfig, ClockMode, ConfigOverrides, LockMode},
+    control::Request,
+    placement::{Anchor, PositionPreset},
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about = "A flip clock for the desktop")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Config file to use, the format is picked from the extension
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<String>,
+
+    /// Window width in logical pixels, the height follows the clock ratio
+    #[arg(long)]
+    pub size: Option<f64>,
+
+    /// Window x position in physical pixels
+    #[arg(long, allow_negative_numbers = true)]
+    pub x: Option<i32>,
+
+    /// Window y position in physical pixels
+    #[arg(long, allow_negative_numbers = true)]
+    pub y: Option<i32>,
+
+    #[arg(long, value_name = "COLOR")]
+    pub dot_color: Option<String>,
+
+    #[arg(long, value_name = "COLOR")]
+    pub card_color: Option<String>,
+
+    #[arg(long, value_name = "COLOR")]
+    pub font_color: Option<String>,
+
+    #[arg(long, value_enum)]
+    pub window_level: Option<WindowLevelArg>,
+
+    /// Place the window at a corner or the center of a monitor instead of at x/y
+    #[arg(long, value_enum)]
+    pub anchor: Option<AnchorArg>,
+
+    /// Monitor used by --anchor, 0 is the primary monitor
+    #[arg(long, requires = "anchor", default_value_t = 0)]
+    pub monitor: usize,
+
+    /// Distance from the monitor edges used by --anchor, in logical pixels
+    #[arg(long, requires = "anchor", default_value_t = 0.0)]
+    pub margin: f32,
+
+    /// What the digits show, the time of day or the days, hours and minutes left
+    #[arg(long, value_enum)]
+    pub mode: Option<ModeArg>,
+
+    /// Show the leading zero of the first digit group as a blank card
+    #[arg(long, conflicts_with = "show_leading_zeros")]
+    pub hide_leading_zeros: bool,
+
+    #[arg(long)]
+    pub show_leading_zeros: bool,
+
+    /// Use one of the profiles defined in the config file
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Lock the window so it can't be dragged or resized
+    #[arg(long, conflicts_with_all = ["unlock", "ghost"])]
+    pub lock: bool,
+
+    #[arg(long, conflicts_with = "ghost")]
+    pub unlock: bool,
+
+    /// Lock the window and let mouse clicks pass through it
+    #[arg(long)]
+    pub ghost: bool,
+
+    /// Write the overrides given on the command line to the config file
+    #[arg(long)]
+    pub save: bool,
+
+    /// Print the effective config and exit
+    #[arg(long)]
+    pub print_config: bool,
+
+    /// Check a config file and exit, the exit code is non-zero when it has errors
+    #[arg(long, value_name = "PATH")]
+    pub validate_config: Option<String>,
+
+    /// Convert the config file to another format and exit
+    #[arg(long, value_name = "FORMAT", value_parser = ["json", "toml", "yaml", "yml"])]
+    pub convert_config: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Send a command to the running clock through its control socket
+    Ctl {
+        #[command(subcommand)]
+        command: CtlCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CtlCommand {
+    /// Print the current config of the running clock
+    State,
+    /// Change the colors, the ones not given stay the same
+    Theme {
+        #[arg(long, value_name = "COLOR")]
+        dot_color: Option<String>,
+        #[arg(long, value_name = "COLOR")]
+        card_color: Option<String>,
+        #[arg(long, value_name = "COLOR")]
+        font_color: Option<String>,
+    },
+    Lock,
+    Unlock,
+    /// Lock the window and let mouse clicks pass through it
+    Ghost,
+    WindowLevel {
+        #[arg(value_enum)]
+        level: WindowLevelArg,
+    },
+    /// Start a timer, the duration is in seconds
+    Timer { seconds: u64 },
+    /// Set an alarm, the time is HH:MM
+    Alarm { time: String },
+}
+
+impl CtlCommand {
+    pub fn to_request(&self) -> Request {
+        match self {
+            CtlCommand::State => Request::GetState,
+            CtlCommand::Theme {
+                dot_color,
+                card_color,
+                font_color,
+            } => Request::SetTheme {
+                dot_color: dot_color.clone(),
+                card_color: card_color.clone(),
+                font_color: font_color.clone(),
+            },
+            CtlCommand::Lock => Request::Lock {
+                mode: LockMode::Locked,
+            },
+            CtlCommand::Unlock => Request::Lock {
+                mode: LockMode::Unlocked,
+            },
+            CtlCommand::Ghost => Request::Lock {
+                mode: LockMode::Ghost,
+            },
+            CtlCommand::WindowLevel { level } => Request::SetWindowLevel {
+                level: level.to_level(),
+            },
+            CtlCommand::Timer { seconds } => Request::StartTimer { seconds: *seconds },
+            CtlCommand::Alarm { time } => Request::SetAlarm { time: time.clone() },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WindowLevelArg {
+    Normal,
+    Top,
+    Bottom,
+}
+
+impl WindowLevelArg {
+    pub fn to_level(self) -> u32 {
+        match self {
+            WindowLevelArg::Normal => 0,
+            WindowLevelArg::Top => 1,
+            WindowLevelArg::Bottom => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ModeArg {
+    Clock,
+    Countdown,
+}
+
+impl ModeArg {
+    pub fn to_mode(self) -> ClockMode {
+        match self {
+            ModeArg::Clock => ClockMode::Clock,
+            ModeArg::Countdown => ClockMode::Countdown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AnchorArg {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+impl AnchorArg {
+    pub fn to_anchor(self) -> Anchor {
+        match self {
+            AnchorArg::TopLeft => Anchor::TopLeft,
+            AnchorArg::TopRight => Anchor::TopRight,
+            AnchorArg::BottomLeft => Anchor::BottomLeft,
+            AnchorArg::BottomRight => Anchor::BottomRight,
+            AnchorArg::Center => Anchor::Center,
+        }
+    }
+}
+
+impl Cli {
+    /// 不能转发给正在运行的时钟的参数，它们只在启动时或者单独使用时有意义
+    ///
+    /// `--config` 和正在运行的时钟使用同一个配置文件时可以转发。
+    pub fn unforwardable_arg(&self) -> Option<&'static str> {
+        let conf_path = AppConfig::get_conf_path();
+        [
+            ("--config", self.config.as_ref().is_some_and(|path| *path != conf_path)),
+            ("--print-config", self.print_config),
+            ("--validate-config", self.validate_config.is_some()),
+            ("--convert-config", self.convert_config.is_some()),
+            ("ctl", self.command.is_some()),
+        ]
+        .into_iter()
+        .find_map(|(arg, is_given)| is_given.then_some(arg))
+    }
+
+    pub fn overrides(&self) -> ConfigOverrides {
+        let lock = if self.lock {
+            Some(LockMode::Locked)
+        } else if self.unlock {
+            Some(LockMode::Unlocked)
+        } else if self.ghost {
+            Some(LockMode::Ghost)
+        } else {
+            None
+        };
+        let hide_leading_zeros = if self.hide_leading_zeros {
+            Some(true)
+        } else if self.show_leading_zeros {
+            Some(false)
+        } else {
+            None
+        };
+
+        ConfigOverrides {
+            dot_color: self.dot_color.clone(),
+            card_color: self.card_color.clone(),
+            font_color: self.font_color.clone(),
+            size: self.size,
+            x: self.x,
+            y: self.y,
+            lock,
+            window_level: self.window_level.map(WindowLevelArg::to_level),
+            mode: self.mode.map(ModeArg::to_mode),
+            hide_leading_zeros,
+            position_preset: self.anchor.map(|anchor| PositionPreset {
+                anchor: anchor.to_anchor(),
+                monitor: self.monitor,
+                margin: self.margin,
+            }),
+            profile: self.profile.clone(),
+        }
+    }
+}
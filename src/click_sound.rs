@@ -0,0 +1,74 @@
+use std::{
+    collections::BTreeSet,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+// 同时翻动的几个数字只响一声
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigitGroup {
+    // 倒计时的天数
+    Days,
+    Hours,
+    Minutes,
+    Seconds,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClickSoundConfig {
+    pub enabled: bool,
+    pub volume: f32,
+    // 翻动时发出声音的数字组，例如只在分钟变化时响
+    pub groups: BTreeSet<DigitGroup>,
+}
+
+impl Default for ClickSoundConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            volume: 0.5,
+            groups: BTreeSet::from([
+                DigitGroup::Days,
+                DigitGroup::Hours,
+                DigitGroup::Minutes,
+                DigitGroup::Seconds,
+            ]),
+        }
+    }
+}
+
+impl ClickSoundConfig {
+    pub fn validate(&self) -> Vec<String> {
+        if (0.0..=1.0).contains(&self.volume) {
+            return vec![];
+        }
+        vec![format!(
+            "click_sound.volume: must be between 0 and 1, got {}",
+            self.volume
+        )]
+    }
+}
+
+/// 限制翻页声音的频率，间隔太短的声音会被丢弃
+#[derive(Debug, Default)]
+pub struct ClickLimiter {
+    last_click: Option<Instant>,
+}
+
+impl ClickLimiter {
+    pub fn try_click(&mut self, now: Instant) -> bool {
+        if self
+            .last_click
+            .is_some_and(|last_click| now.saturating_duration_since(last_click) < MIN_INTERVAL)
+        {
+            return false;
+        }
+        self.last_click = Some(now);
+        true
+    }
+}
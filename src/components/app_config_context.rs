@@ -1,23 +1,48 @@
 use freya::prelude::*;
 
-use crate::{app_config::AppConfig, app_state::use_app_conf};
+use crate::{
+    app_config::{AppConfig, ConfigOverrides},
+    app_state::{use_app_conf, use_app_state},
+    hooks::fetch_current_monitor,
+};
 
 #[allow(non_snake_case)]
 #[component]
 pub fn AppConfigContextProvide(children: Element) -> Element {
-    let mut app_conf = use_app_conf();
-    provide_context(AppConfigContext { app_conf });
+    let (mut app_conf, overrides) = use_app_conf();
+    provide_context(AppConfigContext {
+        app_conf,
+        overrides,
+    });
     let platform = use_platform();
+    let (keep_size, keep_preset) = use_hook(|| {
+        let app_state = use_app_state();
+        (app_state.is_size_overridden(), app_state.is_preset_overridden())
+    });
+
+    // 记录窗口所在的显示器，旧版本的配置在这时换算成逻辑像素
+    let mut update_monitor = move || {
+        spawn(async move {
+            let Some(monitor) = fetch_current_monitor(platform).await else {
+                return;
+            };
+            if app_conf.peek().monitor.is_none() {
+                app_conf.write().convert_to_logical_size(monitor, keep_size, keep_preset);
+            } else if app_conf.peek().monitor.as_ref() != Some(&monitor) {
+                app_conf.write().monitor = Some(monitor);
+            }
+        });
+    };
 
-    let mut scale_factor = use_signal(|| platform.info().window_scale_factor);
-
-    provide_context(ScaleFactorContext(scale_factor));
+    use_hook(move || update_monitor());
 
+    // size 是逻辑像素，缩放比例变化时只需要按新的比例调整窗口，不修改 size
     let handle_scale_change = move |e: ScaleFactorEvent| {
-        let current_scale_factor = e.get_scale_factor();
-        app_conf.write().size =
-            app_conf().size / scale_factor() as f64 * current_scale_factor as f64;
-        scale_factor.set(current_scale_factor);
+        platform.set_window_size_and_position(
+            app_conf.peek().window_size(e.get_scale_factor()),
+            platform.info().window_position,
+        );
+        update_monitor();
     };
 
     rsx!(rect {
@@ -33,11 +58,6 @@ pub fn use_app_conf_context() -> AppConfigContext {
 #[derive(Clone, Copy, PartialEq)]
 pub struct AppConfigContext {
     pub app_conf: Signal<AppConfig>,
-}
-
-#[derive(Clone, Copy, PartialEq)]
-pub struct ScaleFactorContext(pub Signal<f32>);
-
-pub fn use_scale_factor() -> ScaleFactorContext {
-    consume_context::<ScaleFactorContext>()
+    // 命令行给出的、只在本次运行中生效的配置项
+    pub overrides: Signal<ConfigOverrides>,
 }
@@ -0,0 +1,192 @@
+use freya::prelude::*;
+
+use crate::{
+    app_config::ClockMode,
+    components::use_app_conf_context,
+    countdown::{ALARM_LABEL, TIMER_LABEL},
+};
+
+const ITEM_HEIGHT: f32 = 26.0;
+const MENU_WIDTH: f32 = 180.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    ToggleLock,
+    ToggleGhost,
+    CycleWindowLevel,
+    NextProfile,
+    ToggleCountdown,
+    StartStopTimer,
+    ClearAlarm,
+    OpenSettings,
+    ResetPosition,
+    NextPositionPreset,
+    Quit,
+}
+
+#[derive(Props, Clone, PartialEq)]
+pub struct ContextMenuProps {
+    // 右键点击的位置，相对于窗口
+    pub position: Point2D,
+    pub on_action: EventHandler<MenuAction>,
+    pub on_close: EventHandler<()>,
+}
+
+#[allow(non_snake_case)]
+#[component]
+pub fn ContextMenu(props: ContextMenuProps) -> Element {
+    let app_conf = use_app_conf_context().app_conf;
+    let platform = use_platform();
+    let mut selected = use_signal(|| 0usize);
+
+    let app_conf = app_conf();
+    let level_name = match app_conf.window_level {
+        1 => "Always on top",
+        2 => "Always on bottom",
+        _ => "Normal",
+    };
+
+    let preset_name = app_conf
+        .position_preset
+        .map(|preset| format!("{} ({})", preset.anchor.name(), preset.monitor + 1))
+        .unwrap_or("Custom".to_string());
+
+    let mut items = vec![
+        (
+            MenuAction::ToggleLock,
+            if app_conf.lock.is_locked() {
+                "Unlock"
+            } else {
+                "Lock"
+            }
+            .to_string(),
+        ),
+        (MenuAction::ToggleGhost, "Ghost mode".to_string()),
+        (
+            MenuAction::CycleWindowLevel,
+            format!("Window level: {level_name}"),
+        ),
+    ];
+    if !app_conf.profiles.is_empty() {
+        let profile = app_conf.profile.clone().unwrap_or("-".to_string());
+        items.push((MenuAction::NextProfile, format!("Profile: {profile}")));
+    }
+    if !app_conf.countdown.targets.is_empty() {
+        let mode = match app_conf.mode {
+            ClockMode::Clock => "Show countdown",
+            ClockMode::Countdown => "Show clock",
+        };
+        items.push((MenuAction::ToggleCountdown, mode.to_string()));
+    }
+    let timer = if app_conf.countdown.has_target(TIMER_LABEL) {
+        "Stop timer".to_string()
+    } else {
+        format!(
+            "Start timer ({})",
+            timer_duration(app_conf.countdown.timer_seconds)
+        )
+    };
+    items.push((MenuAction::StartStopTimer, timer));
+    if let Some(alarm) = app_conf
+        .countdown
+        .targets
+        .iter()
+        .find(|target| target.label == ALARM_LABEL)
+        .and_then(|target| target.target_time())
+    {
+        items.push((
+            MenuAction::ClearAlarm,
+            format!("Clear alarm {}", alarm.format("%H:%M")),
+        ));
+    }
+    items.extend([
+        (MenuAction::OpenSettings, "Settings".to_string()),
+        (MenuAction::ResetPosition, "Reset position".to_string()),
+        (
+            MenuAction::NextPositionPreset,
+            format!("Position: {preset_name}"),
+        ),
+        (MenuAction::Quit, "Quit".to_string()),
+    ]);
+
+    // 菜单不能超出窗口
+    let PlatformInformation {
+        window_size,
+        window_scale_factor,
+        ..
+    } = platform.info();
+    let window_size = window_size / window_scale_factor;
+    // 窗口放不下所有菜单项时可以滚动
+    let menu_height = (ITEM_HEIGHT * items.len() as f32).min(window_size.height);
+    let left = props.position.x.min(window_size.width - MENU_WIDTH).max(0.0);
+    let top = props.position.y.min(window_size.height - menu_height).max(0.0);
+
+    let item_count = items.len();
+    let actions = items.iter().map(|(action, _)| *action).collect::<Vec<_>>();
+    let onkeydown = move |e: KeyboardEvent| match e.key {
+        Key::ArrowDown => selected.set((selected() + 1) % item_count),
+        Key::ArrowUp => selected.set((selected() + item_count - 1) % item_count),
+        Key::Enter => {
+            props.on_close.call(());
+            props.on_action.call(actions[selected()]);
+        }
+        Key::Escape => props.on_close.call(()),
+        _ => {}
+    };
+
+    let card_color = app_conf.card_color;
+    let font_color = app_conf.font_color;
+
+    rsx!(
+      rect {
+        width: "100%",
+        height: "100%",
+        position: "absolute",
+        onkeydown,
+        onmousedown: move |e: MouseEvent| e.stop_propagation(),
+        onclick: move |_| props.on_close.call(()),
+        rect {
+          width: MENU_WIDTH.to_string(),
+          position: "absolute",
+          position_left: left.to_string(),
+          position_top: top.to_string(),
+          background: card_color.clone(),
+          border: "1 solid {font_color}",
+          corner_radius: "6",
+          shadow: "0 2 8 0 rgb(0, 0, 0, 0.4)",
+          ScrollView {
+            height: menu_height.to_string(),
+            for (index, (action, name)) in items.into_iter().enumerate() {
+              rect {
+                width: "100%",
+                height: ITEM_HEIGHT.to_string(),
+                padding: "0 10",
+                main_align: "center",
+                corner_radius: "6",
+                background: if index == selected() { font_color.clone() } else { "transparent".to_string() },
+                onmouseenter: move |_| selected.set(index),
+                onclick: move |e: MouseEvent| {
+                    e.stop_propagation();
+                    props.on_close.call(());
+                    props.on_action.call(action);
+                },
+                label {
+                  color: if index == selected() { card_color.clone() } else { font_color.clone() },
+                  "{name}"
+                }
+              }
+            }
+          }
+        }
+      }
+    )
+}
+
+// 计时器的时长，例如 5 min、90 s
+fn timer_duration(seconds: u64) -> String {
+    if seconds % 60 == 0 {
+        format!("{} min", seconds / 60)
+    } else {
+        format!("{seconds} s")
+    }
+}
@@ -0,0 +1,56 @@
+use freya::prelude::*;
+
+use crate::{
+    components::use_app_conf_context,
+    keymap::{effective_keymap, find_conflicts},
+};
+
+#[derive(Props, Clone, PartialEq)]
+pub struct KeymapHelpProps {
+    pub on_close: EventHandler<()>,
+}
+
+#[allow(non_snake_case)]
+#[component]
+pub fn KeymapHelp(props: KeymapHelpProps) -> Element {
+    let app_conf = use_app_conf_context().app_conf;
+    let app_conf = app_conf();
+    let keymap = effective_keymap(&app_conf.keymap);
+    let conflicts = find_conflicts(&keymap);
+    let card_color = app_conf.card_color;
+    let font_color = app_conf.font_color;
+
+    rsx!(
+      rect {
+        width: "100%",
+        height: "100%",
+        position: "absolute",
+        padding: "8",
+        background: card_color,
+        color: font_color.clone(),
+        corner_radius: "8",
+        onmousedown: move |e: MouseEvent| e.stop_propagation(),
+        onclick: move |_| props.on_close.call(()),
+        ScrollView {
+          width: "100%",
+          height: "100%",
+          for (action, key) in keymap {
+            rect {
+              width: "100%",
+              direction: "horizontal",
+              padding: "2 0",
+              label {
+                width: "50%",
+                "{action.description()}"
+              }
+              label {
+                width: "50%",
+                color: if conflicts.iter().any(|(conflict, _)| *conflict == key) { "red".to_string() } else { font_color.clone() },
+                "{key}"
+              }
+            }
+          }
+        }
+      }
+    )
+}
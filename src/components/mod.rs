@@ -1,15 +1,21 @@
 mod app_config_context;
+mod context_menu;
 mod cursor_icon_context;
+mod keymap_help;
 mod num;
 mod num_group;
+mod settings_panel;
 mod splitter;
 mod svg;
 mod tools;
 mod window_drag_resize_area;
 
 pub use app_config_context::*;
+pub use context_menu::*;
 pub use cursor_icon_context::*;
+pub use keymap_help::*;
 pub use num_group::*;
+pub use settings_panel::*;
 pub use splitter::*;
 pub use tools::*;
 pub use window_drag_resize_area::*;
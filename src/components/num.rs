@@ -3,12 +3,17 @@ use freya::prelude::*;
 use skia_safe::utils::View3D;
 use skia_safe::{Color, Font, FontStyle, Paint, Point, RRect, Rect, Size, M44, V3};
 
-use crate::{canvas_utils::CanvasUtils, colors::Parse, AppState};
+use crate::{
+    canvas_utils::CanvasUtils, click_sound::DigitGroup, colors::Parse,
+    components::use_app_conf_context, hooks::use_click_sound,
+};
 
 #[derive(Props, Clone, PartialEq, Debug)]
 pub struct NumProps {
-    pub num: u32,
-    pub max: u32,
+    // 为 None 时显示空白的卡片
+    pub num: Option<u32>,
+    // 为 None 时翻动不发出声音，例如工具栏里的日历倒计时
+    pub group: Option<DigitGroup>,
 }
 
 #[allow(non_snake_case)]
@@ -17,29 +22,45 @@ pub fn Num(props: NumProps) -> Element {
     let mut current_num = use_signal(|| props.num);
     let mut next_num = use_signal(|| props.num);
 
-    let app_conf = consume_context::<AppState>().app_conf;
+    let app_conf = use_app_conf_context().app_conf;
+    let click_sound = use_click_sound();
 
-    let card_color = Color::parse(&app_conf.card_color)
+    let card_color = Color::parse(&app_conf.read().card_color)
         .ok()
         .unwrap_or(Color::BLACK);
 
-    let font_color = Color::parse(&app_conf.font_color)
+    let font_color = Color::parse(&app_conf.read().font_color)
         .ok()
         .unwrap_or(Color::WHITE);
 
-    let animation = use_animation(|ctx| {
-        ctx.with(
-            AnimNum::new(0.0, 180.0)
-                .time(500)
-                .ease(Ease::Out)
-                .function(Function::Back),
-        )
-    });
+    let font_family = app_conf.read().font_family.clone();
+    let flip_duration = app_conf.read().flip_duration;
+    let flip_bounce = app_conf.read().flip_bounce;
+
+    let animation = use_animation_with_dependencies(
+        &(flip_duration, flip_bounce),
+        |ctx, (flip_duration, flip_bounce)| {
+            let function = if flip_bounce {
+                Function::Back
+            } else {
+                Function::Sine
+            };
+            ctx.with(
+                AnimNum::new(0.0, 180.0)
+                    .time(flip_duration)
+                    .ease(Ease::Out)
+                    .function(function),
+            )
+        },
+    );
 
     let angle = animation.get();
 
     if props.num != current_num() && props.num != next_num() && !animation.is_running() {
         animation.start();
+        if let Some(group) = props.group {
+            click_sound.click(group);
+        }
         next_num.set(props.num);
     }
 
@@ -49,8 +70,16 @@ pub fn Num(props: NumProps) -> Element {
     }
 
     let canvas = use_canvas(
-        &(current_num(), angle.read().as_f32(), props.max),
-        move |(num, angle, num_max)| {
+        &(
+            current_num(),
+            // 翻到实际的下一个数字，倒计时的时候数字是减小的
+            next_num(),
+            angle.read().as_f32(),
+            card_color,
+            font_color,
+            font_family,
+        ),
+        move |(current, next, angle, card_color, font_color, font_family)| {
             Box::new(move |canvas, font_collection, region| {
                 canvas.with_restore(|canvas| {
                     canvas.translate((region.origin.x, region.origin.y));
@@ -69,8 +98,6 @@ pub fn Num(props: NumProps) -> Element {
                         card_size,
                     );
 
-                    let current = num;
-                    let next = (num + 1) % (num_max + 1);
                     let radius = width * 0.1;
                     let radii = [
                         (radius, radius).into(),
@@ -86,8 +113,10 @@ pub fn Num(props: NumProps) -> Element {
                     let mut text_paint = Paint::default();
                     text_paint.set_anti_alias(true);
                     text_paint.set_color(font_color);
-                    let typefaces =
-                        font_collection.find_typefaces(&["Times New Roman"], FontStyle::default());
+                    let typefaces = font_collection.find_typefaces(
+                        &[font_family.as_str(), "Times New Roman"],
+                        FontStyle::default(),
+                    );
                     let font = Font::new(
                         typefaces
                             .first()
@@ -95,12 +124,14 @@ pub fn Num(props: NumProps) -> Element {
                         region.size.height,
                     );
 
-                    let draw_card = |num: u32, rect: Rect| {
+                    let draw_card = |num: Option<u32>, rect: Rect| {
                         canvas.with_restore(|canvas| {
                             canvas.clip_rect(rect, None, true);
                             let rounded_rect = RRect::new_rect_radii(rect, &radii);
                             canvas.draw_rrect(rounded_rect, &background_paint);
-                            draw_num(canvas, num, &font, &text_paint, width, height);
+                            if let Some(num) = num {
+                                draw_num(canvas, num, &font, &text_paint, width, height);
+                            }
                         });
                     };
 
@@ -159,16 +190,18 @@ pub fn Num(props: NumProps) -> Element {
 
                         let num = if angle <= 90.0 { current } else { next };
 
-                        draw_num_offset(
-                            canvas,
-                            num,
-                            &font,
-                            &text_paint,
-                            width,
-                            height,
-                            -width / 2.0,
-                            -half_height,
-                        );
+                        if let Some(num) = num {
+                            draw_num_offset(
+                                canvas,
+                                num,
+                                &font,
+                                &text_paint,
+                                width,
+                                height,
+                                -width / 2.0,
+                                -half_height,
+                            );
+                        }
                     });
                 });
             })
@@ -1,45 +1,56 @@
 use super::num::Num;
+use crate::{
+    click_sound::DigitGroup,
+    digit_layout::{digit_count, group_units, split_digits, CARD_WIDTH, GAP},
+};
 use freya::prelude::*;
 
 #[derive(Props, Clone, PartialEq, Debug)]
 pub struct NumGroupProps {
     num: u32,
+    // 决定显示几位数字和每一位的最大值
     max_num: u32,
+    // 高位的 0 显示为空白的卡片
+    hide_leading_zeros: bool,
+    // 所有数字组一起缩小的比例，见 `layout_scale`
+    scale: f32,
+    group: DigitGroup,
 }
 
 #[allow(non_snake_case)]
 #[component]
 pub fn NumGroup(props: NumGroupProps) -> Element {
+    let digits = digit_count(props.max_num);
+    let nums = split_digits(props.num, props.max_num, props.hide_leading_zeros);
+
+    let width = group_units(digits) * CARD_WIDTH * props.scale;
+    let height = 100.0 * props.scale;
+    let card_width = 100.0 / group_units(digits);
+    let gap_width = card_width * GAP;
+
     rsx!(
       rect {
         direction: "horizontal",
-        width: "30%",
-        height: "100%",
-        rect {
-            width: "47.619%",
-            height: "100%",
-            position: "relative",
-            color: "white",
-            background: "transparent",
-            overflow: "none",
-            Num {
-              num: props.num / 10,
-              max: props.max_num / 10,
-            }
+        width: "{width}%",
+        height: "{height}%",
+        for (index, num) in nums.into_iter().enumerate() {
+          if index > 0 {
+            rect { width: "{gap_width}%" }
           }
-          rect {width: "4.7619%"}
           rect {
-            width: "47.619%",
+            key: "{index}",
+            width: "{card_width}%",
             height: "100%",
             position: "relative",
             color: "white",
             background: "transparent",
             overflow: "none",
             Num {
-              num: props.num % 10,
-              max: 9
+              num,
+              group: Some(props.group),
             }
-         }
+          }
+        }
       }
     )
 }
@@ -0,0 +1,332 @@
+use freya::prelude::*;
+use skia_safe::Color;
+
+use crate::{
+    app_config::{to_window_level, AppConfig},
+    colors::Parse,
+    components::use_app_conf_context,
+    constant::{MAX_FLIP_DURATION, MAX_SIZE, MIN_SIZE},
+};
+
+const PRESET_COLORS: [&str; 8] = [
+    "#191919", "#cccccc", "#ffffff", "#e74c3c", "#f39c12", "#2ecc71", "#3498db", "#9b59b6",
+];
+
+// 面板占窗口宽度的百分比，右边露出秒数，预览翻页动画
+const PANEL_WIDTH: f32 = 55.0;
+
+// 空闲渐隐的等待时间最多 10 分钟
+const MAX_IDLE_TIMEOUT: f64 = 600.0;
+
+const WINDOW_LEVELS: [(u32, &str); 3] = [
+    (0, "Normal"),
+    (1, "Always on top"),
+    (2, "Always on bottom"),
+];
+
+#[derive(Props, Clone, PartialEq)]
+pub struct SettingsPanelProps {
+    pub on_close: Option<EventHandler<()>>,
+}
+
+#[allow(non_snake_case)]
+#[component]
+pub fn SettingsPanel(props: SettingsPanelProps) -> Element {
+    let mut app_conf = use_app_conf_context().app_conf;
+    let platform = use_platform();
+    let AppConfig {
+        card_color,
+        font_color,
+        size,
+        window_level,
+        lock,
+        opacity,
+        idle_timeout,
+        idle_opacity,
+        ghost_opacity,
+        font_family,
+        flip_duration,
+        flip_bounce,
+        hide_leading_zeros,
+        click_sound,
+        ..
+    } = app_conf();
+    let click_sound = click_sound.enabled;
+
+    let handle_size = move |percentage: f64| {
+        let size = (MIN_SIZE + (MAX_SIZE - MIN_SIZE) * percentage / 100.0).round();
+        app_conf.write().size = size;
+        let PlatformInformation {
+            window_position,
+            window_scale_factor,
+            ..
+        } = platform.info();
+        platform.set_window_size_and_position(
+            app_conf().window_size(window_scale_factor),
+            window_position,
+        );
+    };
+
+    let handle_window_level = move |level: u32| {
+        app_conf.write().window_level = level;
+        platform.set_window_level(to_window_level(level));
+    };
+
+    let idle_timeout_name = if idle_timeout == 0 {
+        "Off".to_string()
+    } else {
+        format!("{idle_timeout} s")
+    };
+
+    let window_level_name = WINDOW_LEVELS
+        .iter()
+        .find(|(level, _)| *level == window_level)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_default();
+
+    rsx!(
+      rect {
+        width: "{PANEL_WIDTH}%",
+        height: "100%",
+        position: "absolute",
+        background: card_color.clone(),
+        color: font_color.clone(),
+        corner_radius: "8",
+        padding: "8",
+        // 在面板里拖动不移动窗口
+        onmousedown: move |e: MouseEvent| e.stop_propagation(),
+        rect {
+          width: "100%",
+          direction: "horizontal",
+          cross_align: "center",
+          label {
+            width: "flex(1)",
+            font_size: "16",
+            "Settings"
+          }
+          Button {
+            onclick: move |_| {
+                props.on_close.as_ref().map(|f| f.call(()));
+            },
+            label { "Done" }
+          }
+        }
+        ScrollView {
+          width: "100%",
+          height: "flex(1)",
+          SettingRow {
+            name: "Card color",
+            ColorField {
+              value: card_color,
+              on_change: move |color| app_conf.write().card_color = color,
+            }
+          }
+          SettingRow {
+            name: "Font color",
+            ColorField {
+              value: font_color,
+              on_change: move |color| app_conf.write().font_color = color,
+            }
+          }
+          SettingRow {
+            name: "Dot color",
+            ColorField {
+              value: app_conf().dot_color,
+              on_change: move |color| app_conf.write().dot_color = color,
+            }
+          }
+          SettingRow {
+            name: "Size",
+            Slider {
+              width: "200",
+              value: (size - MIN_SIZE) / (MAX_SIZE - MIN_SIZE) * 100.0,
+              onmoved: handle_size,
+            }
+            label { "{size:.0}" }
+          }
+          SettingRow {
+            name: "Window level",
+            Dropdown {
+              value: window_level_name,
+              for (level, name) in WINDOW_LEVELS {
+                DropdownItem {
+                  value: name.to_string(),
+                  onclick: move |_| handle_window_level(level),
+                  label { "{name}" }
+                }
+              }
+            }
+          }
+          SettingRow {
+            name: "Lock",
+            Switch {
+              enabled: lock.is_locked(),
+              ontoggled: move |_| {
+                  let lock = app_conf().lock;
+                  app_conf.write().lock = lock.toggle_lock();
+              },
+            }
+          }
+          SettingRow {
+            name: "Opacity",
+            Slider {
+              width: "200",
+              value: opacity as f64 * 100.0,
+              onmoved: move |percentage: f64| {
+                  app_conf.write().opacity = (percentage / 100.0) as f32;
+              },
+            }
+            label { "{opacity * 100.0:.0}%" }
+          }
+          SettingRow {
+            name: "Fade when idle",
+            Slider {
+              width: "200",
+              value: idle_timeout as f64 / MAX_IDLE_TIMEOUT * 100.0,
+              onmoved: move |percentage: f64| {
+                  app_conf.write().idle_timeout =
+                      (MAX_IDLE_TIMEOUT * percentage / 100.0).round() as u64;
+              },
+            }
+            label { "{idle_timeout_name}" }
+          }
+          SettingRow {
+            name: "Idle opacity",
+            Slider {
+              width: "200",
+              value: idle_opacity as f64 * 100.0,
+              onmoved: move |percentage: f64| {
+                  app_conf.write().idle_opacity = (percentage / 100.0) as f32;
+              },
+            }
+            label { "{idle_opacity * 100.0:.0}%" }
+          }
+          SettingRow {
+            name: "Ghost opacity",
+            Slider {
+              width: "200",
+              value: ghost_opacity as f64 * 100.0,
+              onmoved: move |percentage: f64| {
+                  app_conf.write().ghost_opacity = (percentage / 100.0) as f32;
+              },
+            }
+            label { "{ghost_opacity * 100.0:.0}%" }
+          }
+          SettingRow {
+            name: "Font",
+            Input {
+              value: font_family,
+              onchange: move |font_family| app_conf.write().font_family = font_family,
+            }
+          }
+          SettingRow {
+            name: "Flip duration",
+            Slider {
+              width: "200",
+              value: flip_duration as f64 / MAX_FLIP_DURATION as f64 * 100.0,
+              onmoved: move |percentage: f64| {
+                  app_conf.write().flip_duration =
+                      (MAX_FLIP_DURATION as f64 * percentage / 100.0).round() as u64;
+              },
+            }
+            label { "{flip_duration} ms" }
+          }
+          SettingRow {
+            name: "Flip bounce",
+            Switch {
+              enabled: flip_bounce,
+              ontoggled: move |_| {
+                  let flip_bounce = app_conf().flip_bounce;
+                  app_conf.write().flip_bounce = !flip_bounce;
+              },
+            }
+          }
+          SettingRow {
+            name: "Hide leading zero",
+            Switch {
+              enabled: hide_leading_zeros,
+              ontoggled: move |_| {
+                  let hide_leading_zeros = app_conf().hide_leading_zeros;
+                  app_conf.write().hide_leading_zeros = !hide_leading_zeros;
+              },
+            }
+          }
+          SettingRow {
+            name: "Flip click",
+            Switch {
+              enabled: click_sound,
+              ontoggled: move |_| {
+                  let enabled = app_conf().click_sound.enabled;
+                  app_conf.write().click_sound.enabled = !enabled;
+              },
+            }
+          }
+        }
+      }
+    )
+}
+
+#[allow(non_snake_case)]
+#[component]
+fn SettingRow(name: String, children: Element) -> Element {
+    rsx!(
+      rect {
+        width: "100%",
+        direction: "horizontal",
+        cross_align: "center",
+        padding: "4 0",
+        label {
+          width: "120",
+          "{name}"
+        }
+        {children}
+      }
+    )
+}
+
+#[allow(non_snake_case)]
+#[component]
+fn ColorField(value: String, on_change: EventHandler<String>) -> Element {
+    let mut text = use_signal(|| value.clone());
+
+    // 颜色在别处被修改时，例如外部编辑了配置文件，输入框跟着显示新的颜色
+    use_effect(use_reactive(&value, move |value| {
+        if *text.peek() != value {
+            text.set(value);
+        }
+    }));
+
+    rsx!(
+      rect {
+        direction: "horizontal",
+        cross_align: "center",
+        for color in PRESET_COLORS {
+          rect {
+            width: "18",
+            height: "18",
+            margin: "0 2",
+            corner_radius: "9",
+            background: color,
+            border: if color == value { "2 solid rgb(128, 128, 128)" } else { "none" },
+            onclick: move |_| {
+                text.set(color.to_string());
+                on_change.call(color.to_string());
+            },
+          }
+        }
+        Input {
+          value: text(),
+          theme: theme_with!(InputTheme {
+              width: "90".into(),
+          }),
+          onchange: move |color: String| {
+              // 只有合法的颜色才会应用，输入过程中的半成品不影响预览
+              if Color::parse(&color).is_ok() {
+                  on_change.call(color.clone());
+              }
+              text.set(color);
+          },
+        }
+      }
+    )
+}
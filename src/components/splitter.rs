@@ -1,25 +1,25 @@
 use crate::{
-    components::{use_app_conf_context, use_scale_factor},
-    AppState,
+    components::use_app_conf_context,
+    digit_layout::{CARD_WIDTH, SPLITTER},
 };
 use freya::prelude::*;
 
+/// `scale` 和数字组一起缩小，见 `layout_scale`
 #[allow(non_snake_case)]
 #[component]
-pub fn Splitter() -> Element {
+pub fn Splitter(scale: f32) -> Element {
     let app_config_context = use_app_conf_context();
     let app_conf = app_config_context.app_conf;
-    let scale_factor = use_scale_factor().0;
 
-    let radius = app_conf().size as f32 / scale_factor() * 0.04285 * 0.33333;
-
-    let app_conf = consume_context::<AppState>().app_conf;
-    let dot_color = app_conf.dot_color;
+    let width = SPLITTER * CARD_WIDTH * scale;
+    let height = 25.0 * scale;
+    let radius = app_conf().size as f32 * width / 100.0 * 0.33333;
+    let dot_color = app_conf().dot_color;
 
     rsx!(
       rect {
-        width: "4.285%",
-        height: "25%",
+        width: "{width}%",
+        height: "{height}%",
         direction: "horizontal",
         rect {width: "33.333%"}
         rect {
@@ -1,5 +1,8 @@
 mod close;
 mod locked;
+mod muted;
+mod settings;
+mod sound;
 mod to_bottom;
 mod to_normal;
 mod to_top;
@@ -8,6 +11,9 @@ mod unclocked;
 pub use close::CloseSvg;
 use freya::prelude::*;
 pub use locked::LockedSvg;
+pub use muted::MutedSvg;
+pub use settings::SettingsSvg;
+pub use sound::SoundSvg;
 pub use to_bottom::ToBottomSvg;
 pub use to_normal::ToNormalSvg;
 pub use to_top::ToTopSvg;
@@ -0,0 +1,18 @@
+use super::SvgProps;
+use freya::prelude::*;
+
+#[allow(non_snake_case)]
+#[component]
+pub fn MutedSvg(props: SvgProps) -> Element {
+    rsx!(svg {
+        width: "100%",
+        height: "100%",
+        svg_content: r#"
+          <svg width="100%" height="100%" viewBox="0 0 48 48" fill="none" xmlns="http://www.w3.org/2000/svg">
+            <path d="M24 6V42C17 42 11.7985 32.8391 11.7985 32.8391H6C4.89543 32.8391 4 31.9437 4 30.8391V17.0108C4 15.9062 4.89543 15.0108 6 15.0108H11.7985C11.7985 15.0108 17 6 24 6Z" fill="none" stroke="{props.stroke_color}" stroke-width="4" stroke-linejoin="round"/>
+            <path d="M32 18L44 30" stroke="{props.stroke_color}" stroke-width="4" stroke-linecap="round" stroke-linejoin="round"/>
+            <path d="M44 18L32 30" stroke="{props.stroke_color}" stroke-width="4" stroke-linecap="round" stroke-linejoin="round"/>
+          </svg>
+        "#
+    })
+}
@@ -0,0 +1,18 @@
+use super::SvgProps;
+use freya::prelude::*;
+
+#[allow(non_snake_case)]
+#[component]
+pub fn SoundSvg(props: SvgProps) -> Element {
+    rsx!(svg {
+        width: "100%",
+        height: "100%",
+        svg_content: r#"
+          <svg width="100%" height="100%" viewBox="0 0 48 48" fill="none" xmlns="http://www.w3.org/2000/svg">
+            <path d="M24 6V42C17 42 11.7985 32.8391 11.7985 32.8391H6C4.89543 32.8391 4 31.9437 4 30.8391V17.0108C4 15.9062 4.89543 15.0108 6 15.0108H11.7985C11.7985 15.0108 17 6 24 6Z" fill="none" stroke="{props.stroke_color}" stroke-width="4" stroke-linejoin="round"/>
+            <path d="M32 15C33.2093 16.1098 34.1805 17.4833 34.8316 19.0288C35.4827 20.5743 35.7977 22.2549 35.7541 23.9489C35.7105 25.6428 35.3093 27.3025 34.5806 28.8084C33.8518 30.3143 32.8155 31.6268 31.5479 32.6506" stroke="{props.stroke_color}" stroke-width="4" stroke-linecap="round"/>
+            <path d="M36.7 8.6C38.8 10.4 40.5 12.7 41.7 15.3C42.9 17.9 43.5 20.8 43.5 23.7C43.5 26.6 42.9 29.5 41.7 32.1C40.5 34.7 38.8 37 36.7 38.8" stroke="{props.stroke_color}" stroke-width="4" stroke-linecap="round"/>
+          </svg>
+        "#
+    })
+}
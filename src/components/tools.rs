@@ -1,29 +1,39 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
 use freya::prelude::*;
+use tokio::time::sleep;
 
-use super::svg::*;
+use super::{num::Num, svg::*};
 use crate::{
-    app_state::use_app_conf,
-    components::{use_app_conf_context, use_cursor_icon_context, use_scale_factor},
+    calendar::{format_countdown, UpcomingEvent},
+    components::{use_app_conf_context, use_cursor_icon_context},
 };
 
 #[derive(Props, Clone, PartialEq)]
 pub struct ToolsProps {
     pub locked: bool,
+    pub muted: bool,
     pub window_level: WindowLevel,
+    pub profile: Option<String>,
+    // 倒计时目标的名称
+    pub label: Option<String>,
+    pub next_event: Option<UpcomingEvent>,
     pub on_close_click: Option<EventHandler<()>>,
     pub on_lock_click: Option<EventHandler<()>>,
     pub on_level_click: Option<EventHandler<()>>,
+    pub on_mute_click: Option<EventHandler<()>>,
+    pub on_profile_click: Option<EventHandler<()>>,
+    pub on_settings_click: Option<EventHandler<()>>,
 }
 
 #[allow(non_snake_case)]
 #[component]
 pub fn Tools(props: ToolsProps) -> Element {
-    let app_conf = use_app_conf();
-    let font_color = app_conf().font_color;
     let app_config_context = use_app_conf_context();
     let app_conf = app_config_context.app_conf;
-    let scale_factor = use_scale_factor().0;
-    let margin = app_conf().size as f32 / scale_factor() * 0.02;
+    let font_color = app_conf().font_color;
+    let margin = app_conf().size as f32 * 0.02;
 
     let handle_close = move |e: MouseEvent| {
         e.stop_propagation();
@@ -40,6 +50,21 @@ pub fn Tools(props: ToolsProps) -> Element {
         props.on_level_click.as_ref().map(|f| f.call(()));
     };
 
+    let handle_mute = move |e: MouseEvent| {
+        e.stop_propagation();
+        props.on_mute_click.as_ref().map(|f| f.call(()));
+    };
+
+    let handle_profile = move |e: MouseEvent| {
+        e.stop_propagation();
+        props.on_profile_click.as_ref().map(|f| f.call(()));
+    };
+
+    let handle_settings = move |e: MouseEvent| {
+        e.stop_propagation();
+        props.on_settings_click.as_ref().map(|f| f.call(()));
+    };
+
     let platform = use_platform();
     let mut is_hovering = use_signal(|| false);
     let mut cursor_icon_context = use_cursor_icon_context();
@@ -60,6 +85,8 @@ pub fn Tools(props: ToolsProps) -> Element {
 
     let icon_width = "5.714%";
     let icon_height = "80%";
+    let has_profiles = !app_conf().profiles.is_empty();
+    let profile_name = props.profile.clone().unwrap_or("-".to_string());
 
     rsx!(
       rect{
@@ -114,6 +141,67 @@ pub fn Tools(props: ToolsProps) -> Element {
             onmouseleave,
             WindowLevelIcon{ window_level: props.window_level, stroke_color: font_color.clone() }
           }
+          rect {
+            width: icon_width,
+            height: icon_height,
+            onclick: handle_mute,
+            onmouseenter,
+            onmouseleave,
+            if props.muted {
+                MutedSvg {
+                  stroke_color: font_color.clone()
+                }
+            } else {
+                SoundSvg {
+                  stroke_color: font_color.clone()
+                }
+            }
+          }
+          rect {
+            width: icon_width,
+            height: icon_height,
+            onclick: handle_settings,
+            onmouseenter,
+            onmouseleave,
+            SettingsSvg {
+              stroke_color: font_color.clone()
+            }
+          }
+          if has_profiles {
+            rect {
+              height: icon_height,
+              main_align: "center",
+              padding: "0 {margin}",
+              onclick: handle_profile,
+              onmouseenter,
+              onmouseleave,
+              label {
+                color: font_color.clone(),
+                font_size: (margin * 2.0).to_string(),
+                "{profile_name}"
+              }
+            }
+          }
+          if let Some(countdown_label) = props.label.clone() {
+            rect {
+              height: icon_height,
+              main_align: "center",
+              padding: "0 {margin}",
+              label {
+                color: font_color.clone(),
+                font_size: (margin * 2.0).to_string(),
+                "{countdown_label}"
+              }
+            }
+          }
+          if let Some(event) = props.next_event.clone() {
+            NextEventLabel {
+              title: event.title,
+              start: event.start,
+              color: font_color.clone(),
+              font_size: margin * 2.0,
+            }
+          }
         }
       }
     )
@@ -128,3 +216,65 @@ pub fn WindowLevelIcon(window_level: WindowLevel, stroke_color: String) -> Eleme
         WindowLevel::AlwaysOnTop => rsx!(ToTopSvg { stroke_color }),
     }
 }
+
+/// 日历中下一个事件的标题和翻页的倒计时，每秒刷新一次
+#[allow(non_snake_case)]
+#[component]
+pub fn NextEventLabel(
+    title: String,
+    start: DateTime<Utc>,
+    color: String,
+    font_size: f32,
+) -> Element {
+    let mut now = use_signal(Utc::now);
+
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                sleep(Duration::from_millis(1000)).await;
+                now.set(Utc::now());
+            }
+        });
+    });
+
+    let countdown = format_countdown(start - now());
+    // 倒计时的每一位数字是一张小的翻页卡片
+    let card_width = font_size * 0.8;
+
+    rsx!(
+      rect {
+        height: "80%",
+        direction: "horizontal",
+        main_align: "center",
+        cross_align: "center",
+        padding: "0 {font_size / 2.0}",
+        label {
+          color: color.clone(),
+          font_size: font_size.to_string(),
+          max_lines: "1",
+          "{title} · "
+        }
+        for (index, char) in countdown.chars().enumerate() {
+          if let Some(digit) = char.to_digit(10) {
+            rect {
+              key: "{index}",
+              width: "{card_width}",
+              height: "90%",
+              margin: "0 1",
+              Num {
+                num: Some(digit),
+                group: None,
+              }
+            }
+          } else {
+            label {
+              key: "{index}",
+              color: color.clone(),
+              font_size: font_size.to_string(),
+              "{char}"
+            }
+          }
+        }
+      }
+    )
+}
@@ -1,47 +1,15 @@
-use std::sync::atomic::{AtomicI64, Ordering};
-
 use freya::prelude::*;
-use mouce::{common::MouseButton, common::MouseEvent, Mouse};
-use tokio::sync::broadcast::channel;
 
 use crate::{
     components::use_cursor_icon_context,
-    hooks::{use_prop, use_prop_with_option_default},
+    constant::MIN_SIZE,
+    hooks::{use_prop, use_prop_with_option_default, UseSnap},
+    resize_geometry::{resize, resize_delta, ResizeConstraints, WindowGeometry},
+    snap::snap_resize,
 };
 
 const EDGE_SIZE: f32 = 10.0;
 
-const NORTH_DIRECTION: [ResizeDirection; 3] = [
-    ResizeDirection::North,
-    ResizeDirection::NorthEast,
-    ResizeDirection::NorthWest,
-];
-
-const SOUTH_DIRECTION: [ResizeDirection; 3] = [
-    ResizeDirection::South,
-    ResizeDirection::SouthEast,
-    ResizeDirection::SouthWest,
-];
-
-const WEST_DIRECTION: [ResizeDirection; 3] = [
-    ResizeDirection::West,
-    ResizeDirection::NorthWest,
-    ResizeDirection::SouthWest,
-];
-
-const EAST_DIRECTION: [ResizeDirection; 3] = [
-    ResizeDirection::East,
-    ResizeDirection::NorthEast,
-    ResizeDirection::SouthEast,
-];
-
-const CORNER_DIRECTION: [ResizeDirection; 4] = [
-    ResizeDirection::NorthEast,
-    ResizeDirection::NorthWest,
-    ResizeDirection::SouthEast,
-    ResizeDirection::SouthWest,
-];
-
 const ALL_DIRECTION_CURSOR_ICON: [CursorIcon; 8] = [
     CursorIcon::EResize,
     CursorIcon::NResize,
@@ -58,7 +26,8 @@ pub struct WindowDragResizeAreaProps {
     pub enable: Option<bool>,
     pub edge_size: Option<f32>,
     pub aspect_ratio: Option<f32>,
-    // work only aspect_ratio is not None
+    pub snap: Option<UseSnap>,
+    pub snap_distance: Option<f32>,
     pub on_size_change: Option<EventHandler<(Size2D, Size2D)>>,
     pub min_width: Option<f32>,
     pub max_width: Option<f32>,
@@ -71,29 +40,80 @@ pub struct WindowDragResizeAreaProps {
 #[component]
 pub fn WindowDragResizeArea(props: WindowDragResizeAreaProps) -> Element {
     let platform = use_platform();
+    let platform_information = use_platform_information();
     let mut resize_direction = use_signal(|| None as Option<ResizeDirection>);
     let mut start_resize = use_signal(|| false);
+    // 开始调整大小时的方向、窗口大小和位置
+    let mut resize_start = use_signal(|| None as Option<(ResizeDirection, Size2D, Point2D)>);
+    // 窗口管理器不能调整大小时自己跟踪鼠标：方向、开始时的窗口和鼠标在屏幕上的物理坐标
+    let mut pointer_resize =
+        use_signal(|| None as Option<(ResizeDirection, WindowGeometry, Point2D)>);
     let enable = props.enable.unwrap_or(true);
     let mut cursor_icon_context = use_cursor_icon_context();
 
     let edge_size = use_prop(props.edge_size.unwrap_or(EDGE_SIZE));
     let aspect_ratio = use_prop(props.aspect_ratio);
     let on_size_change = use_prop(props.on_size_change);
-    let min_width = use_prop_with_option_default(props.min_width, 200.0);
+    let snap = use_prop(props.snap);
+    let snap_distance = use_prop(props.snap_distance.unwrap_or(0.0));
+    let min_width = use_prop_with_option_default(props.min_width, MIN_SIZE as f32);
     let max_width = use_prop_with_option_default(props.max_width, f32::MAX);
-    let min_height = use_prop_with_option_default(props.min_height, 200.0);
+    let min_height = use_prop_with_option_default(props.min_height, MIN_SIZE as f32);
     let max_height = use_prop_with_option_default(props.max_height, f32::MAX);
 
     if !enable {
         resize_direction.set(None);
     }
 
+    // 按鼠标相对于开始时移动的距离调整窗口，修正宽高比、大小限制和吸附
+    let apply_resize =
+        move |direction: ResizeDirection, delta: (f32, f32), start: WindowGeometry| {
+            let PlatformInformation {
+                window_size,
+                window_position,
+                ..
+            } = platform.info();
+            let constraints = ResizeConstraints {
+                aspect_ratio: aspect_ratio(),
+                min_width: min_width(),
+                max_width: max_width(),
+                min_height: min_height(),
+                max_height: max_height(),
+            };
+            let mut geometry = resize(direction, delta, start, constraints);
+
+            if let Some(snap) = snap() {
+                if snap_distance() > 0.0 && !snap.is_bypassed() {
+                    geometry = snap_resize(
+                        direction,
+                        geometry,
+                        &snap.lines(),
+                        snap_distance(),
+                        constraints,
+                    );
+                }
+            }
+            let WindowGeometry {
+                size: new_window_size,
+                position: new_window_position,
+            } = geometry;
+
+            if new_window_size != window_size || new_window_position != window_position {
+                platform.set_window_size_and_position(new_window_size, new_window_position);
+            }
+            if let Some(on_size_change) = on_size_change() {
+                on_size_change.call((new_window_size, start.size));
+            }
+        };
+
     let onmouseover = move |e: freya::prelude::MouseEvent| {
         if !enable {
             return;
         }
+        // 窗口管理器调整大小的过程中收不到鼠标事件，再次收到时说明已经松开了鼠标
         if start_resize() {
-            return;
+            start_resize.set(false);
+            resize_start.set(None);
         }
 
         let PlatformInformation {
@@ -129,166 +149,108 @@ pub fn WindowDragResizeArea(props: WindowDragResizeAreaProps) -> Element {
     };
 
     let onmousedown = move |e: freya::prelude::MouseEvent| {
-        if aspect_ratio().is_none() && resize_direction().is_some() {
-            e.stop_propagation();
-            platform.drag_resize_window(resize_direction().unwrap());
+        let Some(direction) = resize_direction() else {
             return;
-        }
-        if resize_direction().is_some() {
-            e.stop_propagation();
+        };
+        e.stop_propagation();
+        let PlatformInformation {
+            window_size,
+            window_position,
+            window_scale_factor,
+            ..
+        } = platform.info();
+        let start = WindowGeometry {
+            size: window_size,
+            position: window_position,
+        };
+        start_resize.set(true);
+
+        // 交给窗口管理器调整大小，鼠标离开窗口之后也能继续调整，之后再修正宽高比
+        match platform.drag_resize_window(direction) {
+            Ok(_) => resize_start.set(Some((direction, window_size, window_position))),
+            // 不支持的平台（例如 macOS）按住鼠标时窗口仍然收到移动事件，由 onglobalmouseover 跟踪
+            Err(err) => {
+                log::debug!("drag_resize_window failed, tracking the pointer instead: {err:?}");
+                let pointer = screen_position(
+                    window_position,
+                    e.get_screen_coordinates().to_f32(),
+                    window_scale_factor,
+                );
+                pointer_resize.set(Some((direction, start, pointer)));
+            }
         }
     };
 
-    use_effect(move || {
-        let (tx, mut rx) = channel::<mouce::common::MouseEvent>(100);
-        let mut mouse_manager = Mouse::new();
-        // 这个时间是用来约束报告率过高的鼠标，鼠标如果报告速度小于5ms，那么界面会出现卡顿
-        let last_move_time = AtomicI64::default();
-
-        mouse_manager
-            .hook(Box::new(move |e| match e {
-                MouseEvent::Press(MouseButton::Left) | MouseEvent::Release(MouseButton::Left) => {
-                    tx.send(e.clone()).ok();
-                }
-                MouseEvent::AbsoluteMove(_, _) => {
-                    let last_time = last_move_time.load(Ordering::Relaxed);
-                    let current_time = chrono::Local::now().timestamp_millis();
-                    if last_time == 0 {
-                        last_move_time.store(current_time, Ordering::Relaxed);
-                        tx.send(e.clone()).ok();
-                        return;
-                    }
-
-                    let diff = current_time - last_time;
-
-                    if diff > 5 {
-                        last_move_time.store(current_time, Ordering::Relaxed);
-                        tx.send(e.clone()).ok();
-                    }
-                }
-                _ => {}
-            }))
-            .ok();
-
-        spawn(async move {
-            let mut last_position = mouse_manager.get_position().unwrap();
-            let PlatformInformation {
-                mut window_size,
-                mut window_position,
-                ..
-            } = platform.info();
-
-            loop {
-                let e = rx.recv().await.ok();
-                if e.is_none() || aspect_ratio().is_none() || resize_direction().is_none() {
-                    continue;
-                }
-                match e.unwrap() {
-                    MouseEvent::AbsoluteMove(x, y) => {
-                        if !start_resize() {
-                            continue;
-                        }
-
-                        let delta_x = (x - last_position.0) as f32;
-                        let delta_y = (y - last_position.1) as f32;
-
-                        if delta_x == 0.0 && delta_y == 0.0 {
-                            continue;
-                        }
-
-                        let resize_direction = resize_direction().unwrap();
-
-                        let mut new_window_size = window_size;
-                        let mut new_window_position = window_position;
-
-                        //包含上
-                        if NORTH_DIRECTION.contains(&resize_direction) {
-                            new_window_size.height = new_window_size.height - delta_y;
-                            new_window_position.y = new_window_position.y + delta_y;
-                        }
-
-                        //包含下
-                        if SOUTH_DIRECTION.contains(&resize_direction) {
-                            new_window_size.height = new_window_size.height + delta_y;
-                        }
-
-                        //包含左
-                        if WEST_DIRECTION.contains(&resize_direction) {
-                            new_window_size.width = new_window_size.width - delta_x;
-                            new_window_position.x = new_window_position.x + delta_x;
-                        }
-
-                        //包含右
-                        if EAST_DIRECTION.contains(&resize_direction) {
-                            new_window_size.width = new_window_size.width + delta_x;
-                        }
-
-                        let min_width = min_width();
-                        let max_width = max_width();
-                        let mut min_height = min_height();
-                        let mut max_height = max_height();
-
-                        if let Some(aspect_ratio) = aspect_ratio() {
-                            min_height = min_width / aspect_ratio;
-                            max_height = max_width / aspect_ratio;
-                        }
-                        new_window_size.width = new_window_size.width.clamp(min_width, max_width);
-                        new_window_size.height =
-                            new_window_size.height.clamp(min_height, max_height);
+    // 鼠标在窗口外时坐标也是相对于窗口的，加上窗口的位置换算到屏幕上，窗口移动之后距离仍然正确
+    let onglobalmouseover = move |e: freya::prelude::MouseEvent| {
+        let Some((direction, start, start_pointer)) = pointer_resize() else {
+            return;
+        };
+        let PlatformInformation {
+            window_position,
+            window_scale_factor,
+            ..
+        } = platform.info();
+        let pointer = screen_position(
+            window_position,
+            e.get_screen_coordinates().to_f32(),
+            window_scale_factor,
+        );
+        apply_resize(
+            direction,
+            (pointer.x - start_pointer.x, pointer.y - start_pointer.y),
+            start,
+        );
+    };
 
-                        if let Some(aspect_ratio) = aspect_ratio() {
-                            if CORNER_DIRECTION.contains(&resize_direction) {
-                                new_window_size.height = new_window_size.width / aspect_ratio;
-                            } else if window_size.width != new_window_size.width {
-                                new_window_size.height = new_window_size.width / aspect_ratio;
-                            } else {
-                                new_window_size.width = new_window_size.height * aspect_ratio;
-                            }
-                        }
+    // 松开鼠标时结束调整。窗口管理器调整的过程中可能收不到这个事件，由下一次 onmouseover 结束
+    let onglobalmouseup = move |_| {
+        if start_resize() {
+            start_resize.set(false);
+            resize_start.set(None);
+        }
+        if pointer_resize.peek().is_some() {
+            pointer_resize.set(None);
+        }
+    };
 
-                        if new_window_size.width != window_size.width
-                            || new_window_size.height != window_size.height
-                        {
-                            platform
-                                .set_window_size_and_position(new_window_size, new_window_position);
-                            if let Some(on_size_change) = on_size_change() {
-                                on_size_change.call((new_window_size, window_size));
-                            }
-                        }
+    use_effect(move || {
+        let PlatformInformation { window_size, .. } = platform_information();
+        let Some((direction, start_size, start_position)) = *resize_start.peek() else {
+            return;
+        };
+        if window_size == start_size {
+            return;
+        }
 
-                        //更新鼠标位置
-                        last_position.0 = x;
-                        last_position.1 = y;
-                        window_size = new_window_size;
-                        window_position = new_window_position;
-                    }
-                    MouseEvent::Press(MouseButton::Left) => {
-                        if resize_direction().is_some() {
-                            last_position = mouse_manager.get_position().unwrap();
-                            let info = platform.info();
-                            window_size = info.window_size;
-                            window_position = info.window_position;
-                            start_resize.set(true);
-                        }
-                    }
-                    MouseEvent::Release(MouseButton::Left) => {
-                        start_resize.set(false);
-                        resize_direction.set(None);
-                    }
-                    _ => {}
-                };
-            }
-        });
+        apply_resize(
+            direction,
+            resize_delta(direction, start_size, window_size),
+            WindowGeometry {
+                size: start_size,
+                position: start_position,
+            },
+        );
     });
 
     rsx!(rect {
         onmousedown,
         onmouseover,
         onmouseleave,
+        onglobalmouseover,
+        onglobalmouseup,
         { props.children }
     })
 }
 
+/// 鼠标在屏幕上的物理坐标，`position` 是相对于窗口的逻辑坐标
+fn screen_position(window_position: Point2D, position: Point2D, scale_factor: f32) -> Point2D {
+    Point2D::new(
+        window_position.x + position.x * scale_factor,
+        window_position.y + position.y * scale_factor,
+    )
+}
+
 fn cursor_resize_direction(
     win_size: Size2D,
     position: Point2D,
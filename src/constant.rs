@@ -1 +1,6 @@
 pub const RATIO: f32 = 2.8;
+// 窗口宽度的范围，单位是逻辑像素
+pub const MIN_SIZE: f64 = 200.0;
+pub const MAX_SIZE: f64 = 2000.0;
+// 翻页动画最长的时长，单位毫秒
+pub const MAX_FLIP_DURATION: u64 = 2000;
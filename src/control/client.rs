@@ -0,0 +1,8 @@
+use std::os::unix::net::UnixStream;
+
+use super::{exchange, socket_path, Request, Response};
+
+/// 把一条命令发送给正在运行的时钟，等待它的回复
+pub fn send_request(request: &Request) -> std::io::Result<Response> {
+    exchange(UnixStream::connect(socket_path())?, request)
+}
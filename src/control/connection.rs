@@ -0,0 +1,45 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use freya::prelude::*;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt};
+
+use super::{execute, Request, Response};
+use crate::components::AppConfigContext;
+
+/// 逐行读取 JSON 命令并回复，直到对方关闭连接
+pub async fn serve_connection(
+    stream: impl AsyncRead + AsyncWrite,
+    app_config_context: AppConfigContext,
+    platform: UsePlatform,
+) {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = tokio::io::BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => execute(request, app_config_context, platform),
+            Err(e) => Response::error(format!("invalid command: {e}")),
+        };
+
+        let mut response = serde_json::to_string(&response).unwrap();
+        response.push('\n');
+        if writer.write_all(response.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// 发送一条命令并等待回复，客户端使用阻塞的连接
+pub fn exchange(mut stream: impl Read + Write, request: &Request) -> std::io::Result<Response> {
+    let mut line = serde_json::to_string(request).unwrap();
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response)?;
+    serde_json::from_str(&response)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
@@ -0,0 +1,458 @@
+use std::time::Duration;
+
+use chrono::Local;
+use freya::prelude::*;
+use serde::Serialize;
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{unbounded_channel, UnboundedSender},
+    time::{sleep, timeout},
+};
+
+use super::{execute, Request, Response, ACCEPT_RETRY_INTERVAL};
+use crate::{
+    app_config::{AppConfig, ClockMode, LockMode},
+    components::{AppConfigContext, MenuAction},
+    countdown::{ALARM_LABEL, TIMER_LABEL},
+};
+
+// 请求体最大的长度，只接收简单的 JSON 命令
+const MAX_BODY_SIZE: usize = 64 * 1024;
+// 请求行和每个请求头最大的长度，以及请求头最多的个数
+const MAX_LINE_SIZE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 64;
+// 读取整个请求和写回复的最长时间，避免慢速客户端一直占着连接，单位秒
+const IO_TIMEOUT: u64 = 10;
+// POST 请求必须带上的请求头，值不限，例如 `curl -H 'X-Flip-Clock: 1'`
+const CLIENT_HEADER: &str = "x-flip-clock";
+
+/// `GET /state` 返回的时钟状态
+#[derive(Debug, Clone, Serialize)]
+struct Status {
+    // 数字显示的内容，时钟或者倒计时
+    mode: ClockMode,
+    lock: LockMode,
+    window_level: u32,
+    profile: Option<String>,
+    // 计时器剩余的秒数，没有计时器或者已经结束时为空
+    timer_remaining: Option<u64>,
+    // 闹钟的时间，RFC 3339 格式
+    alarms: Vec<String>,
+    config: AppConfig,
+}
+
+impl Status {
+    fn from_config(app_conf: &AppConfig) -> Self {
+        Self {
+            mode: app_conf.mode,
+            lock: app_conf.lock,
+            window_level: app_conf.window_level,
+            profile: app_conf.profile.clone(),
+            timer_remaining: app_conf
+                .countdown
+                .targets
+                .iter()
+                .find(|target| target.label == TIMER_LABEL)
+                .and_then(|target| target.target_time())
+                .map(|time| (time - Local::now()).num_seconds())
+                .filter(|seconds| *seconds > 0)
+                .map(|seconds| seconds as u64),
+            alarms: app_conf
+                .countdown
+                .targets
+                .iter()
+                .filter(|target| target.label == ALARM_LABEL)
+                .map(|target| target.time.clone())
+                .collect(),
+            config: app_conf.clone(),
+        }
+    }
+}
+
+/// 在 `127.0.0.1:http_port` 上提供状态查询和控制接口，端口为 0 时不启动
+///
+/// 写操作和工具栏、右键菜单的动作一致，由 `on_action` 在界面中执行
+pub fn use_http_server(
+    app_config_context: AppConfigContext,
+    mut on_action: impl FnMut(MenuAction) + 'static,
+) {
+    let app_conf = app_config_context.app_conf;
+    let platform = use_platform();
+    let mut task = use_signal(|| None as Option<Task>);
+    let actions = use_hook(|| {
+        let (tx, mut rx) = unbounded_channel::<MenuAction>();
+        spawn(async move {
+            while let Some(action) = rx.recv().await {
+                on_action(action);
+            }
+        });
+        tx
+    });
+
+    let http_port = app_conf().http_port;
+    use_effect(use_reactive(&http_port, move |http_port| {
+        if let Some(task) = task.write().take() {
+            task.cancel();
+        }
+        if http_port == 0 {
+            return;
+        }
+
+        let actions = actions.clone();
+        task.set(Some(spawn(async move {
+            let listener = match TcpListener::bind(("127.0.0.1", http_port)).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    log::warn!("failed to start http server on port {http_port}: {e}");
+                    return;
+                }
+            };
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        log::warn!("failed to accept http connection: {e}");
+                        sleep(Duration::from_millis(ACCEPT_RETRY_INTERVAL)).await;
+                        continue;
+                    }
+                };
+                let actions = actions.clone();
+                spawn(handle_connection(stream, http_port, move |request| {
+                    route(&request, app_config_context, platform, &actions)
+                }));
+            }
+        })));
+    }));
+}
+
+/// 读取到的一个请求
+#[derive(Debug)]
+struct HttpRequest {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// 请求不合法时直接回复的状态码和原因
+type Rejection = (u16, &'static str);
+
+/// 读取一个请求，检查通过之后交给 `route` 处理，回复之后关闭连接
+async fn handle_connection(
+    stream: TcpStream,
+    port: u16,
+    route: impl FnOnce(HttpRequest) -> (u16, String),
+) {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    let request = timeout(Duration::from_secs(IO_TIMEOUT), read_request(&mut reader))
+        .await
+        .unwrap_or(Err((408, "request timed out")));
+    let (status, body) = match request {
+        Ok(request) => match check_request(&request, port) {
+            Ok(()) => route(request),
+            Err((status, error)) => (status, error_body(error)),
+        },
+        Err((status, error)) => (status, error_body(error)),
+    };
+
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        431 => "Request Header Fields Too Large",
+        _ => "OK",
+    };
+    let response = format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = timeout(Duration::from_secs(IO_TIMEOUT), writer.write_all(response.as_bytes())).await;
+}
+
+async fn read_request(reader: &mut (impl AsyncBufRead + Unpin)) -> Result<HttpRequest, Rejection> {
+    let request_line = read_line(reader).await?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = vec![];
+    loop {
+        let header = read_line(reader).await?;
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err((431, "too many headers"));
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err((400, "invalid header"));
+        };
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let mut request = HttpRequest {
+        method,
+        path,
+        headers,
+        body: vec![],
+    };
+    let content_length = match request.header("content-length") {
+        Some(length) => length
+            .parse()
+            .map_err(|_| (400, "invalid content length"))?,
+        None => 0,
+    };
+    if content_length > MAX_BODY_SIZE {
+        return Err((413, "request body is too large"));
+    }
+    request.body = vec![0; content_length];
+    reader
+        .read_exact(&mut request.body)
+        .await
+        .map_err(|_| (400, "incomplete request body"))?;
+    Ok(request)
+}
+
+// 每一行最多读取 `MAX_LINE_SIZE` 字节，避免一直读取没有换行的数据
+async fn read_line(reader: &mut (impl AsyncBufRead + Unpin)) -> Result<String, Rejection> {
+    let mut line = String::new();
+    let read = reader
+        .take(MAX_LINE_SIZE as u64)
+        .read_line(&mut line)
+        .await
+        .map_err(|_| (400, "invalid request"))?;
+    if read == 0 {
+        return Err((400, "incomplete request"));
+    }
+    if !line.ends_with('\n') {
+        return Err((431, "request line is too long"));
+    }
+    Ok(line)
+}
+
+/// 只接受本机的客户端
+///
+/// 浏览器里的网页也能访问 127.0.0.1，所以 Host 必须是本机地址（防止 DNS 重绑定），不能带 Origin，
+/// POST 请求还要带上 `X-Flip-Clock` 头，跨域的网页不经过预检请求就不能设置这个头
+fn check_request(request: &HttpRequest, port: u16) -> Result<(), Rejection> {
+    let host = request.header("host").unwrap_or_default();
+    let local_host = [format!("127.0.0.1:{port}"), format!("localhost:{port}")]
+        .iter()
+        .any(|local| host.eq_ignore_ascii_case(local));
+    if !local_host {
+        return Err((403, "host is not allowed"));
+    }
+    if request.header("origin").is_some() {
+        return Err((403, "cross-origin requests are not allowed"));
+    }
+    if request.method == "POST" {
+        if request.header(CLIENT_HEADER).is_none() {
+            return Err((403, "missing X-Flip-Clock header"));
+        }
+        // 只有 `/command` 有请求体，必须是 JSON
+        let is_json = request
+            .header("content-type")
+            .and_then(|content_type| content_type.split(';').next())
+            .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"));
+        if request.path == "/command" && !is_json {
+            return Err((415, "content type must be application/json"));
+        }
+    }
+    Ok(())
+}
+
+fn route(
+    request: &HttpRequest,
+    app_config_context: AppConfigContext,
+    platform: UsePlatform,
+    actions: &UnboundedSender<MenuAction>,
+) -> (u16, String) {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/state") => (
+            200,
+            serde_json::to_string(&Status::from_config(&app_config_context.app_conf.peek()))
+                .unwrap(),
+        ),
+        // 和控制 socket 使用同样的 JSON 命令
+        ("POST", "/command") => match serde_json::from_slice::<Request>(&request.body) {
+            Ok(request) => {
+                let response = execute(request, app_config_context, platform);
+                let status = if response.ok { 200 } else { 400 };
+                (status, serde_json::to_string(&response).unwrap())
+            }
+            Err(e) => (400, error_body(&format!("invalid command: {e}"))),
+        },
+        (_, "/state" | "/command") => (405, error_body("method not allowed")),
+        ("POST", path) => {
+            let Some(action) = path_action(path) else {
+                return (404, error_body("not found"));
+            };
+            let _ = actions.send(action);
+            (202, r#"{"ok":true}"#.to_string())
+        }
+        _ => (404, error_body("not found")),
+    }
+}
+
+// 工具栏和右键菜单中的动作
+fn path_action(path: &str) -> Option<MenuAction> {
+    Some(match path {
+        "/actions/toggle-lock" => MenuAction::ToggleLock,
+        "/actions/toggle-ghost" => MenuAction::ToggleGhost,
+        "/actions/cycle-window-level" => MenuAction::CycleWindowLevel,
+        "/actions/next-profile" => MenuAction::NextProfile,
+        "/actions/toggle-countdown" => MenuAction::ToggleCountdown,
+        "/actions/start-stop-timer" => MenuAction::StartStopTimer,
+        "/actions/clear-alarm" => MenuAction::ClearAlarm,
+        "/actions/reset-position" => MenuAction::ResetPosition,
+        "/actions/next-position-preset" => MenuAction::NextPositionPreset,
+        "/actions/quit" => MenuAction::Quit,
+        _ => return None,
+    })
+}
+
+fn error_body(error: &str) -> String {
+    serde_json::to_string(&Response::error(error)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 用本机的客户端发送一个原始请求，`{port}` 替换成服务端的端口，返回状态行
+    async fn send(request: &str) -> String {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = async {
+            let (stream, _) = listener.accept().await.unwrap();
+            handle_connection(stream, port, |request| {
+                (200, format!("{} {}", request.method, request.path))
+            })
+            .await;
+        };
+        let client = async {
+            let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+            let request = request.replace("{port}", &port.to_string());
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            let _ = stream.read_to_string(&mut response).await;
+            response
+        };
+        let (_, response) = tokio::join!(server, client);
+        response.lines().next().unwrap_or_default().to_string()
+    }
+
+    #[tokio::test]
+    async fn local_hosts_are_accepted() {
+        for host in ["127.0.0.1", "localhost", "LOCALHOST"] {
+            let request = format!("GET /state HTTP/1.1\r\nHost: {host}:{{port}}\r\n\r\n");
+            assert_eq!(send(&request).await, "HTTP/1.1 200 OK", "{host}");
+        }
+    }
+
+    #[tokio::test]
+    async fn other_hosts_are_rejected() {
+        for request in [
+            "GET /state HTTP/1.1\r\nHost: attacker.example:{port}\r\n\r\n",
+            "GET /state HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n",
+            "GET /state HTTP/1.1\r\nHost: 127.0.0.1:1\r\n\r\n",
+            "GET /state HTTP/1.1\r\n\r\n",
+        ] {
+            assert_eq!(send(request).await, "HTTP/1.1 403 Forbidden", "{request}");
+        }
+    }
+
+    #[tokio::test]
+    async fn cross_origin_requests_are_rejected() {
+        let request =
+            "GET /state HTTP/1.1\r\nHost: 127.0.0.1:{port}\r\nOrigin: http://127.0.0.1:{port}\r\n\r\n";
+        assert_eq!(send(request).await, "HTTP/1.1 403 Forbidden");
+    }
+
+    #[tokio::test]
+    async fn post_requires_client_header() {
+        let request = "POST /actions/toggle-lock HTTP/1.1\r\nHost: 127.0.0.1:{port}\r\n\r\n";
+        assert_eq!(send(request).await, "HTTP/1.1 403 Forbidden");
+
+        let request = "POST /actions/toggle-lock HTTP/1.1\r\nHost: 127.0.0.1:{port}\r\nX-Flip-Clock: 1\r\n\r\n";
+        assert_eq!(send(request).await, "HTTP/1.1 200 OK");
+    }
+
+    #[tokio::test]
+    async fn command_requires_json() {
+        let body = r#"{"cmd":"get_state"}"#;
+        for (content_type, status) in [
+            ("", "HTTP/1.1 415 Unsupported Media Type"),
+            (
+                "Content-Type: text/plain\r\n",
+                "HTTP/1.1 415 Unsupported Media Type",
+            ),
+            ("Content-Type: application/json\r\n", "HTTP/1.1 200 OK"),
+            (
+                "Content-Type: application/json; charset=utf-8\r\n",
+                "HTTP/1.1 200 OK",
+            ),
+        ] {
+            let request = format!(
+                "POST /command HTTP/1.1\r\nHost: 127.0.0.1:{{port}}\r\nX-Flip-Clock: 1\r\n{content_type}Content-Length: {}\r\n\r\n{body}",
+                body.len()
+            );
+            assert_eq!(send(&request).await, status, "{content_type}");
+        }
+    }
+
+    #[tokio::test]
+    async fn oversized_requests_are_rejected() {
+        let request = format!(
+            "GET /state HTTP/1.1\r\nHost: 127.0.0.1:{{port}}\r\nX-Padding: {}\r\n\r\n",
+            "a".repeat(MAX_LINE_SIZE)
+        );
+        assert_eq!(
+            send(&request).await,
+            "HTTP/1.1 431 Request Header Fields Too Large"
+        );
+
+        let request = format!(
+            "POST /command HTTP/1.1\r\nHost: 127.0.0.1:{{port}}\r\nX-Flip-Clock: 1\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_SIZE + 1
+        );
+        assert_eq!(send(&request).await, "HTTP/1.1 413 Payload Too Large");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_requests_time_out() {
+        let request = "GET /state HTTP/1.1\r\nHost: 127.0.0.1:{port}\r\n";
+        assert_eq!(send(request).await, "HTTP/1.1 408 Request Timeout");
+    }
+
+    #[test]
+    fn status_reports_mode_and_lock() {
+        let app_conf = AppConfig {
+            mode: ClockMode::Countdown,
+            lock: LockMode::Ghost,
+            ..AppConfig::default()
+        };
+        let status = serde_json::to_value(Status::from_config(&app_conf)).unwrap();
+        assert_eq!(status["mode"], "countdown");
+        assert_eq!(status["lock"], "ghost");
+    }
+}
@@ -0,0 +1,89 @@
+mod connection;
+mod http;
+mod protocol;
+
+#[cfg(unix)]
+mod client;
+#[cfg(unix)]
+mod server;
+
+#[cfg(unix)]
+use std::path::PathBuf;
+
+use freya::prelude::*;
+
+use crate::{app_config::to_window_level, components::AppConfigContext};
+
+pub use connection::{exchange, serve_connection};
+pub use http::use_http_server;
+pub use protocol::*;
+
+#[cfg(unix)]
+pub use client::send_request;
+#[cfg(unix)]
+pub use server::use_control_socket;
+
+// accept 出错时（例如文件描述符用完）等一会儿再重试，避免空转占满 CPU，单位毫秒
+pub const ACCEPT_RETRY_INTERVAL: u64 = 200;
+
+/// 控制 socket 的路径，优先放在 `XDG_RUNTIME_DIR` 下
+///
+/// 没有 `XDG_RUNTIME_DIR` 时放在临时目录中每个用户自己的子目录里，监听时这个目录只允许本人访问。
+#[cfg(unix)]
+pub fn socket_path() -> PathBuf {
+    let dir = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => std::env::temp_dir().join(format!("flip_clock-{}", unsafe { libc::getuid() })),
+    };
+    dir.join("flip_clock.sock")
+}
+
+/// 执行一条命令，修改界面使用的配置，窗口的大小、位置和层级变化时同时修改窗口
+pub fn execute(
+    request: Request,
+    app_config_context: AppConfigContext,
+    platform: UsePlatform,
+) -> Response {
+    let AppConfigContext {
+        mut app_conf,
+        mut overrides,
+    } = app_config_context;
+    log::debug!("control command {request:?}");
+    // 再次启动时把已经运行的窗口放到前面
+    if matches!(request, Request::Forward { .. }) {
+        platform.with_window(|window| window.focus_window());
+    }
+
+    let old_conf = app_conf.peek().clone();
+    let mut new_conf = old_conf.clone();
+    let mut new_overrides = overrides.peek().clone();
+    let response = request.apply(&mut new_conf, &mut new_overrides);
+    // 保存配置时按照覆盖项还原，所以要在修改配置之前更新
+    if *overrides.peek() != new_overrides {
+        overrides.set(new_overrides);
+    }
+    if (new_conf.size, new_conf.x, new_conf.y) != (old_conf.size, old_conf.x, old_conf.y) {
+        platform.set_window_size_and_position(
+            new_conf.window_size(platform.info().window_scale_factor),
+            Point2D::new(new_conf.x as f32, new_conf.y as f32),
+        );
+    }
+    if new_conf.window_level != old_conf.window_level {
+        platform.set_window_level(to_window_level(new_conf.window_level));
+    }
+    if new_conf != old_conf {
+        app_conf.set(new_conf);
+    }
+    response
+}
+
+#[cfg(not(unix))]
+pub fn send_request(_request: &Request) -> std::io::Result<Response> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "the control socket is only available on Unix",
+    ))
+}
+
+#[cfg(not(unix))]
+pub fn use_control_socket(_app_config_context: AppConfigContext) {}
@@ -0,0 +1,166 @@
+use chrono::{Local, NaiveTime, TimeDelta};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use skia_safe::Color;
+
+use crate::{
+    app_config::{AppConfig, ClockMode, ConfigOverrides, LockMode},
+    cli::Cli,
+    colors::Parse,
+    countdown::{next_time_of_day, ALARM_LABEL, TIMER_LABEL},
+};
+
+/// 控制 socket 接收的命令，每行一个 JSON 对象，例如 `{"cmd":"lock","mode":"ghost"}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    GetState,
+    SetTheme {
+        #[serde(default)]
+        dot_color: Option<String>,
+        #[serde(default)]
+        card_color: Option<String>,
+        #[serde(default)]
+        font_color: Option<String>,
+    },
+    Lock {
+        mode: LockMode,
+    },
+    SetWindowLevel {
+        level: u32,
+    },
+    // 从现在开始倒计时 `seconds` 秒
+    StartTimer {
+        seconds: u64,
+    },
+    // 倒计时到下一次 `time`，格式为 HH:MM
+    SetAlarm {
+        time: String,
+    },
+    // 再次启动时转发过来的命令行参数，不包括程序名，没有 `--save` 时只在本次运行中生效
+    Forward {
+        args: Vec<String>,
+    },
+}
+
+/// 每个命令对应一行回复，成功时带上执行之后的配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<AppConfig>,
+}
+
+impl Response {
+    pub fn ok(state: AppConfig) -> Self {
+        Self {
+            ok: true,
+            error: None,
+            state: Some(state),
+        }
+    }
+
+    pub fn error(error: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            error: Some(error.into()),
+            state: None,
+        }
+    }
+}
+
+impl Request {
+    /// 把命令应用到配置上，命令不合法时配置保持不变
+    ///
+    /// `overrides` 是本次运行中不写入配置文件的配置项，转发过来的命令行参数会修改它
+    pub fn apply(self, app_conf: &mut AppConfig, overrides: &mut ConfigOverrides) -> Response {
+        match self {
+            Request::GetState => {}
+            Request::SetTheme {
+                dot_color,
+                card_color,
+                font_color,
+            } => {
+                for (name, color) in [
+                    ("dot_color", &dot_color),
+                    ("card_color", &card_color),
+                    ("font_color", &font_color),
+                ] {
+                    if let Some(color) = color {
+                        if Color::parse(color).is_err() {
+                            return Response::error(format!("{name}: invalid color \"{color}\""));
+                        }
+                    }
+                }
+                if let Some(dot_color) = dot_color {
+                    app_conf.dot_color = dot_color;
+                }
+                if let Some(card_color) = card_color {
+                    app_conf.card_color = card_color;
+                }
+                if let Some(font_color) = font_color {
+                    app_conf.font_color = font_color;
+                }
+            }
+            Request::Lock { mode } => app_conf.lock = mode,
+            Request::SetWindowLevel { level } => {
+                if level > 2 {
+                    return Response::error(format!("level: must be 0, 1 or 2, got {level}"));
+                }
+                app_conf.window_level = level;
+            }
+            Request::Forward { args } => {
+                let args = std::iter::once("flip_clock".to_string()).chain(args);
+                let cli = match Cli::try_parse_from(args) {
+                    Ok(cli) => cli,
+                    Err(e) => return Response::error(e.to_string()),
+                };
+                if let Some(arg) = cli.unforwardable_arg() {
+                    return Response::error(format!(
+                        "{arg} can't be forwarded to the running clock"
+                    ));
+                }
+                let new_overrides = cli.overrides();
+                if let Err(errors) = new_overrides.validate() {
+                    return Response::error(errors.join("\n"));
+                }
+                if let Some(profile) = &new_overrides.profile {
+                    if !app_conf.profiles.contains_key(profile) {
+                        return Response::error(format!("unknown profile: {profile}"));
+                    }
+                }
+                new_overrides.apply(app_conf);
+                // 和启动时一样，指定了 --save 的配置项写入配置文件
+                if cli.save {
+                    overrides.remove(&new_overrides);
+                } else {
+                    overrides.merge(new_overrides);
+                }
+            }
+            Request::StartTimer { seconds } => {
+                let Some(time) = i64::try_from(seconds)
+                    .ok()
+                    .and_then(TimeDelta::try_seconds)
+                    .and_then(|seconds| Local::now().checked_add_signed(seconds))
+                else {
+                    return Response::error(format!("seconds: {seconds} is too large"));
+                };
+                app_conf.countdown.set_target(TIMER_LABEL, time);
+                app_conf.mode = ClockMode::Countdown;
+            }
+            Request::SetAlarm { time } => {
+                let Some(alarm) = NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                    .ok()
+                    .and_then(|time| next_time_of_day(Local::now(), time))
+                else {
+                    return Response::error(format!("time: expected HH:MM, got \"{time}\""));
+                };
+                app_conf.countdown.set_target(ALARM_LABEL, alarm);
+                app_conf.mode = ClockMode::Countdown;
+            }
+        }
+        Response::ok(app_conf.clone())
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    cell::Cell,
+    fs::{DirBuilder, Permissions},
+    io,
+    os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt},
+    path::Path,
+    rc::Rc,
+    time::Duration,
+};
+
+use freya::prelude::*;
+use tokio::{
+    net::{UnixListener, UnixStream},
+    time::sleep,
+};
+
+use super::{serve_connection, socket_path, ACCEPT_RETRY_INTERVAL};
+use crate::components::AppConfigContext;
+
+/// 监听控制 socket，收到的命令直接修改界面使用的配置
+pub fn use_control_socket(app_config_context: AppConfigContext) {
+    let platform = use_platform();
+    // 只删除自己创建的 socket
+    let owns_socket = use_hook(|| Rc::new(Cell::new(false)));
+
+    let bound = owns_socket.clone();
+    use_effect(move || {
+        let bound = bound.clone();
+        spawn(async move {
+            let Some(listener) = bind(&socket_path()).await else {
+                return;
+            };
+            bound.set(true);
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        log::warn!("failed to accept control connection: {e}");
+                        sleep(Duration::from_millis(ACCEPT_RETRY_INTERVAL)).await;
+                        continue;
+                    }
+                };
+                spawn(serve_connection(stream, app_config_context, platform));
+            }
+        });
+    });
+
+    use_drop(move || {
+        if owns_socket.get() {
+            let _ = std::fs::remove_file(socket_path());
+        }
+    });
+}
+
+async fn bind(path: &Path) -> Option<UnixListener> {
+    if let Some(dir) = path.parent() {
+        if let Err(e) = private_dir(dir) {
+            log::warn!("control socket directory {}: {e}", dir.display());
+            return None;
+        }
+    }
+    // 能连上说明另一个时钟正在使用这个 socket，连不上的是上次没有删除的旧文件
+    if UnixStream::connect(path).await.is_ok() {
+        log::warn!("control socket {} is used by another clock", path.display());
+        return None;
+    }
+    if let Err(e) = remove_stale_socket(path) {
+        log::warn!("control socket {}: {e}", path.display());
+        return None;
+    }
+    let listener = UnixListener::bind(path)
+        .map_err(|e| log::warn!("failed to bind control socket {}: {e}", path.display()))
+        .ok()?;
+    // 只有自己能发送控制命令
+    if let Err(e) = std::fs::set_permissions(path, Permissions::from_mode(0o600)) {
+        log::warn!("failed to restrict control socket {}: {e}", path.display());
+        let _ = std::fs::remove_file(path);
+        return None;
+    }
+    Some(listener)
+}
+
+// 创建只有自己能访问的目录，已经存在的目录必须属于自己并且其他人不能访问
+fn private_dir(dir: &Path) -> io::Result<()> {
+    DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    let metadata = std::fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() || metadata.uid() != unsafe { libc::getuid() } {
+        return Err(io::Error::other("not a directory owned by the current user"));
+    }
+    if metadata.mode() & 0o077 != 0 {
+        return Err(io::Error::other("accessible by other users"));
+    }
+    Ok(())
+}
+
+// 只删除自己留下的 socket，路径上的其他文件保持不动
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !metadata.file_type().is_socket() || metadata.uid() != unsafe { libc::getuid() } {
+        return Err(io::Error::other("not a socket owned by the current user"));
+    }
+    std::fs::remove_file(path)
+}
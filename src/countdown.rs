@@ -0,0 +1,169 @@
+use chrono::{
+    DateTime, Days, Local, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeDelta, TimeZone,
+};
+use serde::{Deserialize, Serialize};
+
+// 天数最多显示三位
+pub const MAX_DAYS: u32 = 999;
+
+// 计时器和闹钟作为倒计时目标保存，名称相同的目标会被替换
+pub const TIMER_LABEL: &str = "Timer";
+pub const ALARM_LABEL: &str = "Alarm";
+
+/// 倒计时结束之后做什么
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CountdownFinish {
+    // 停在 000:00:00
+    #[default]
+    Hold,
+    // 窗口闪烁，然后停在 000:00:00
+    Flash,
+    // 切换回时钟
+    Clock,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CountdownTarget {
+    // 显示在工具栏上的名称，例如 "Release"
+    #[serde(default)]
+    pub label: String,
+    // 目标时间，格式为 YYYY-MM-DD HH:MM、YYYY-MM-DD 或 RFC 3339
+    pub time: String,
+    #[serde(default)]
+    pub on_finish: CountdownFinish,
+}
+
+impl CountdownTarget {
+    pub fn target_time(&self) -> Option<DateTime<Local>> {
+        parse_target(&self.time)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CountdownConfig {
+    pub targets: Vec<CountdownTarget>,
+    // 当前显示的目标的序号，点击数字时切换到下一个
+    pub active: usize,
+    // 快捷键和右键菜单启动的计时器的时长，单位秒
+    pub timer_seconds: u64,
+}
+
+impl Default for CountdownConfig {
+    fn default() -> Self {
+        Self {
+            targets: vec![],
+            active: 0,
+            timer_seconds: 300,
+        }
+    }
+}
+
+impl CountdownConfig {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = self
+            .targets
+            .iter()
+            .enumerate()
+            .filter(|(_, target)| target.target_time().is_none())
+            .map(|(index, target)| {
+                format!(
+                    "countdown.targets[{index}].time: expected YYYY-MM-DD HH:MM, got \"{}\"",
+                    target.time
+                )
+            })
+            .collect::<Vec<_>>();
+        if self.timer_seconds == 0 {
+            errors.push("countdown.timer_seconds: must be at least 1 second".to_string());
+        }
+        errors
+    }
+
+    pub fn has_target(&self, label: &str) -> bool {
+        self.targets.iter().any(|target| target.label == label)
+    }
+
+    /// 当前显示的目标，序号超出范围时使用第一个
+    pub fn active_target(&self) -> Option<&CountdownTarget> {
+        self.targets.get(self.active).or(self.targets.first())
+    }
+
+    pub fn next_target(&mut self) {
+        if !self.targets.is_empty() {
+            self.active = (self.active + 1) % self.targets.len();
+        }
+    }
+
+    /// 添加目标并切换到这个目标，已经有同名的目标时替换它，用于控制命令设置的计时器和闹钟
+    pub fn set_target(&mut self, label: &str, time: DateTime<Local>) {
+        let target = CountdownTarget {
+            label: label.to_string(),
+            time: time.to_rfc3339_opts(SecondsFormat::Secs, false),
+            on_finish: CountdownFinish::Flash,
+        };
+        match self.targets.iter().position(|target| target.label == label) {
+            Some(index) => {
+                self.targets[index] = target;
+                self.active = index;
+            }
+            None => {
+                self.targets.push(target);
+                self.active = self.targets.len() - 1;
+            }
+        }
+    }
+
+    /// 删除名称为 `label` 的目标，返回是否有这个目标
+    pub fn remove_target(&mut self, label: &str) -> bool {
+        let Some(index) = self.targets.iter().position(|target| target.label == label) else {
+            return false;
+        };
+        self.targets.remove(index);
+        if self.active > index || self.active >= self.targets.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+        true
+    }
+}
+
+/// `now` 之后下一次到达 `time` 的时间，今天已经过了就是明天
+pub fn next_time_of_day(now: DateTime<Local>, time: NaiveTime) -> Option<DateTime<Local>> {
+    let today = now.date_naive();
+    [today, today.checked_add_days(Days::new(1))?]
+        .into_iter()
+        .filter_map(|day| Local.from_local_datetime(&day.and_time(time)).earliest())
+        .find(|alarm| *alarm > now)
+}
+
+/// 没有时区的时间按本地时间计算
+pub fn parse_target(time: &str) -> Option<DateTime<Local>> {
+    let time = time.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(time) {
+        return Some(time.with_timezone(&Local));
+    }
+    let time = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(time, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })?;
+    Local.from_local_datetime(&time).earliest()
+}
+
+/// 剩余时间对应的天、小时和分钟
+///
+/// 分钟向上取整，所以到达目标时间的那一刻才显示 000:00:00。超过 999 天时显示 999:23:59。
+pub fn countdown_digits(remaining: TimeDelta) -> (u32, u32, u32) {
+    let minutes = (remaining.num_seconds().max(0) + 59) / 60;
+    let days = minutes / (24 * 60);
+    if days > MAX_DAYS as i64 {
+        return (MAX_DAYS, 23, 59);
+    }
+    (
+        days as u32,
+        (minutes / 60 % 24) as u32,
+        (minutes % 60) as u32,
+    )
+}
@@ -0,0 +1,133 @@
+// 时钟数字的布局以一张数字卡片的宽度为单位
+
+// 同一组里两张卡片之间的间隔
+pub const GAP: f32 = 0.1;
+// 两个数字组之间的分隔符
+pub const SPLITTER: f32 = 0.3;
+// 一张卡片占整行宽度的百分比，来自原来 时:分:秒 六张卡片的布局
+pub const CARD_WIDTH: f32 = 100.0 / 7.0;
+// 原来的布局一共占多少个单位，更宽的布局按比例缩小到这个宽度
+const BASE_UNITS: f32 = 3.0 * (2.0 + GAP) + 2.0 * SPLITTER;
+
+/// 显示 0 到 `max_num` 需要几位数字，至少两位
+pub fn digit_count(max_num: u32) -> u32 {
+    max_num.max(10).ilog10() + 1
+}
+
+/// 一个数字组占多少个单位
+pub fn group_units(digits: u32) -> f32 {
+    digits as f32 + digits.saturating_sub(1) as f32 * GAP
+}
+
+/// 几个数字组放在同一行时的缩放比例
+///
+/// 数字变多时卡片的宽和高一起缩小，卡片的形状和窗口的宽高比都保持不变。
+pub fn layout_scale(groups: &[u32]) -> f32 {
+    let units = groups
+        .iter()
+        .map(|&digits| group_units(digits))
+        .sum::<f32>()
+        + groups.len().saturating_sub(1) as f32 * SPLITTER;
+    (BASE_UNITS / units).min(1.0)
+}
+
+/// 第 `position` 位（从右往左，从 0 开始）能显示的最大数字
+///
+/// 更高的位都已经是最大值时这一位受 `max_num` 限制，否则可以是 0 到 9。例如 `max_num` 为 23 时
+/// 十位最大是 2，十位是 2 时个位最大是 3，十位是 0 或 1 时个位最大是 9。
+pub fn digit_max(max_num: u32, position: u32, higher_at_max: bool) -> u32 {
+    if higher_at_max {
+        max_num / 10u32.pow(position) % 10
+    } else {
+        9
+    }
+}
+
+/// 把数字拆到 [`digit_count`] 张卡片上，从高位到低位排列
+///
+/// 每一位按 [`digit_max`] 限制，某一位超出时它和更低的位都显示最大值，所以超出 `max_num` 时显示
+/// `max_num`。`hide_leading_zeros` 为 true 时高位的 0 为 None，显示为空白的卡片，最低位总是显示。
+pub fn split_digits(num: u32, max_num: u32, hide_leading_zeros: bool) -> Vec<Option<u32>> {
+    let digits = digit_count(max_num);
+    // 卡片放不下的高位不为 0 时已经超出
+    let mut clamped = 10u32
+        .checked_pow(digits)
+        .is_some_and(|capacity| num >= capacity);
+    let mut at_max = true;
+    let mut leading = hide_leading_zeros;
+    (0..digits)
+        .rev()
+        .map(|position| {
+            let max_digit = digit_max(max_num, position, at_max);
+            let mut digit = num / 10u32.pow(position) % 10;
+            if clamped || digit > max_digit {
+                clamped = true;
+                digit = max_digit;
+            }
+            at_max = at_max && digit == max_digit;
+            leading = leading && digit == 0 && position > 0;
+            (!leading).then_some(digit)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digit_max_depends_on_higher_digits() {
+        assert_eq!(digit_max(23, 1, true), 2);
+        assert_eq!(digit_max(23, 0, true), 3);
+        assert_eq!(digit_max(23, 0, false), 9);
+        assert_eq!(digit_max(59, 1, true), 5);
+        // max_num 只有一位时十位只能是 0
+        assert_eq!(digit_max(5, 1, true), 0);
+    }
+
+    #[test]
+    fn split_digits_in_range() {
+        assert_eq!(split_digits(7, 59, false), [Some(0), Some(7)]);
+        assert_eq!(split_digits(19, 23, false), [Some(1), Some(9)]);
+        assert_eq!(split_digits(23, 23, false), [Some(2), Some(3)]);
+        assert_eq!(split_digits(42, 999, false), [Some(0), Some(4), Some(2)]);
+        assert_eq!(split_digits(u32::MAX, u32::MAX, false).len(), 10);
+    }
+
+    #[test]
+    fn split_digits_clamps_each_digit() {
+        // 十位超出，个位跟着显示最大值
+        assert_eq!(split_digits(31, 23, false), [Some(2), Some(3)]);
+        // 十位正好是最大值，个位超出
+        assert_eq!(split_digits(27, 23, false), [Some(2), Some(3)]);
+        assert_eq!(split_digits(60, 59, false), [Some(5), Some(9)]);
+        assert_eq!(split_digits(1234, 999, false), [Some(9), Some(9), Some(9)]);
+        assert_eq!(split_digits(7, 5, false), [Some(0), Some(5)]);
+    }
+
+    #[test]
+    fn split_digits_hides_leading_zeros() {
+        assert_eq!(split_digits(7, 23, true), [None, Some(7)]);
+        assert_eq!(split_digits(0, 23, true), [None, Some(0)]);
+        assert_eq!(split_digits(10, 23, true), [Some(1), Some(0)]);
+        assert_eq!(split_digits(5, 999, true), [None, None, Some(5)]);
+        assert_eq!(split_digits(105, 999, true), [Some(1), Some(0), Some(5)]);
+    }
+
+    #[test]
+    fn layout_scale_keeps_the_original_size() {
+        // 时:分:秒 是原来的布局，不缩放
+        assert_eq!(layout_scale(&[2, 2, 2]), 1.0);
+        // 更窄的布局不放大
+        assert_eq!(layout_scale(&[2, 2]), 1.0);
+    }
+
+    #[test]
+    fn layout_scale_shrinks_wider_layouts() {
+        let scale = layout_scale(&[3, 2, 2]);
+        assert!(scale < 1.0);
+        let units = group_units(3) + 2.0 * group_units(2) + 2.0 * SPLITTER;
+        assert!((scale * units - BASE_UNITS).abs() < 1e-4);
+        assert!(layout_scale(&[4, 2, 2]) < scale);
+    }
+}
@@ -0,0 +1,166 @@
+use std::{process::Stdio, time::Duration};
+
+use chrono::{Local, Timelike};
+use freya::prelude::*;
+use serde::{Deserialize, Serialize};
+use tokio::{process::Command, time::sleep};
+
+use crate::{app_config::AppConfig, process::kill_group};
+
+// 检查整点的间隔
+const CHECK_INTERVAL: u64 = 1000;
+
+/// 可以绑定命令的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    OnAlarm,
+    OnTimerDone,
+    OnHour,
+    OnLockChange,
+    OnCountdownDone,
+}
+
+impl HookEvent {
+    /// 传给命令的 `FLIPCLOCK_EVENT` 环境变量
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::OnAlarm => "alarm",
+            HookEvent::OnTimerDone => "timer_done",
+            HookEvent::OnHour => "hour",
+            HookEvent::OnLockChange => "lock_change",
+            HookEvent::OnCountdownDone => "countdown_done",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct UseEventHooks {
+    app_conf: Signal<AppConfig>,
+}
+
+impl UseEventHooks {
+    /// 运行事件绑定的命令，`vars` 会以 `FLIPCLOCK_EVENT_<NAME>` 的形式传给命令
+    pub fn fire(&self, event: HookEvent, vars: Vec<(&str, String)>) {
+        let app_conf = self.app_conf.peek();
+        let Some(command) = app_conf.event_hooks.get(&event).cloned() else {
+            return;
+        };
+        if command.trim().is_empty() {
+            return;
+        }
+        let timeout = Duration::from_secs(app_conf.hook_timeout);
+
+        let mut envs = vec![
+            ("FLIPCLOCK_EVENT".to_string(), event.name().to_string()),
+            (
+                "FLIPCLOCK_EVENT_TIME".to_string(),
+                Local::now().format("%H:%M:%S").to_string(),
+            ),
+        ];
+        envs.extend(
+            vars.into_iter()
+                .map(|(name, value)| (format!("FLIPCLOCK_EVENT_{}", name.to_uppercase()), value)),
+        );
+
+        tokio::spawn(run_command(event, command, envs, timeout));
+    }
+}
+
+/// 监听整点和锁定状态的变化，运行配置中绑定的命令
+pub fn use_event_hooks(app_conf: Signal<AppConfig>) -> UseEventHooks {
+    let event_hooks = UseEventHooks { app_conf };
+    let mut last_lock = use_signal(|| app_conf.peek().lock);
+
+    let lock = app_conf().lock;
+    use_effect(use_reactive(&lock, move |lock| {
+        if *last_lock.peek() == lock {
+            return;
+        }
+        last_lock.set(lock);
+        let lock = serde_json::to_value(lock).unwrap();
+        event_hooks.fire(
+            HookEvent::OnLockChange,
+            vec![("lock", lock.as_str().unwrap_or_default().to_string())],
+        );
+    }));
+
+    use_effect(move || {
+        spawn(async move {
+            let mut last_hour = Local::now().hour();
+            loop {
+                sleep(Duration::from_millis(CHECK_INTERVAL)).await;
+                let hour = Local::now().hour();
+                if hour != last_hour {
+                    last_hour = hour;
+                    event_hooks.fire(HookEvent::OnHour, vec![("hour", hour.to_string())]);
+                }
+            }
+        });
+    });
+
+    event_hooks
+}
+
+async fn run_command(
+    event: HookEvent,
+    command: String,
+    envs: Vec<(String, String)>,
+    timeout: Duration,
+) {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    // 命令放在单独的进程组中，超时之后连同它启动的子进程一起结束，而不只是外面的 sh 或 cmd。
+    // 不使用 kill_on_drop，Windows 上 cmd 先被结束的话 taskkill 就找不到它的子进程了
+    #[cfg(unix)]
+    shell.process_group(0);
+    #[cfg(windows)]
+    shell.creation_flags(windows_sys::Win32::System::Threading::CREATE_NEW_PROCESS_GROUP);
+    let child = shell
+        .arg(&command)
+        .envs(envs)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            log::warn!("{}: failed to run `{command}`: {e}", event.name());
+            return;
+        }
+    };
+    let pid = child.id();
+
+    let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            log::warn!("{}: `{command}` failed: {e}", event.name());
+            return;
+        }
+        Err(_) => {
+            log::warn!("{}: `{command}` timed out after {timeout:?}", event.name());
+            if let Some(pid) = pid {
+                kill_group(pid).await;
+            }
+            return;
+        }
+    };
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        log::info!("{}: {line}", event.name());
+    }
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        log::warn!("{}: {line}", event.name());
+    }
+    if !output.status.success() {
+        log::warn!("{}: `{command}` exited with {}", event.name(), output.status);
+    }
+}
@@ -1,3 +1,25 @@
+mod use_audio;
+mod use_calendar;
+mod use_chime;
+mod use_click_sound;
+mod use_countdown;
+mod use_flash;
+mod use_idle_fade;
+mod use_monitors;
 mod use_prop;
+mod use_snap;
+mod use_window_animation;
+mod use_window_placement;
 
+pub use use_audio::*;
+pub use use_calendar::*;
+pub use use_chime::*;
+pub use use_click_sound::*;
+pub use use_countdown::*;
+pub use use_flash::*;
+pub use use_idle_fade::*;
+pub use use_monitors::*;
 pub use use_prop::*;
+pub use use_snap::*;
+pub use use_window_animation::*;
+pub use use_window_placement::*;
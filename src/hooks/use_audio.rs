@@ -0,0 +1,58 @@
+use std::{cell::RefCell, rc::Rc};
+
+use freya::prelude::*;
+
+use crate::{
+    app_config::AppConfig,
+    audio::{load_user_sounds, AudioPlayer, UserSounds},
+    chime::{AudioSink, ChimeConfig, ChimeSound},
+};
+
+/// 整个窗口共用的音频输出，翻页声音、报时和日历提醒都通过它播放
+#[derive(Clone)]
+pub struct UseAudio {
+    // 第一次播放的时候才打开音频设备，打不开时记为 None，之后不再重试
+    player: Rc<RefCell<Option<Option<AudioPlayer>>>>,
+    // `sound_dir` 中的声音，目录变化时在后台重新读取，读取完成之前使用内置的声音
+    user_sounds: Rc<RefCell<UserSounds>>,
+}
+
+impl UseAudio {
+    /// 在后台依次播放多个声音，`sound_dir` 中的同名文件优先于内置的声音
+    pub fn play(&self, names: &[String], volume: f32) {
+        let mut player = self.player.borrow_mut();
+        if let Some(player) = player.get_or_insert_with(AudioPlayer::new) {
+            player.play(names, volume, &self.user_sounds.borrow());
+        }
+    }
+}
+
+impl AudioSink for UseAudio {
+    fn play(&mut self, sounds: &[ChimeSound], config: &ChimeConfig) {
+        let names = sounds.iter().map(ChimeSound::name).collect::<Vec<_>>();
+        UseAudio::play(self, &names, config.volume);
+    }
+}
+
+/// 创建共用的音频输出，在 `MyApp` 中调用一次，再传给需要播放声音的 hook
+pub fn use_audio(app_conf: Signal<AppConfig>) -> UseAudio {
+    let audio = use_hook(|| UseAudio {
+        player: Rc::new(RefCell::new(None)),
+        user_sounds: Rc::new(RefCell::new(UserSounds::new())),
+    });
+    let mut task = use_signal(|| None as Option<Task>);
+
+    let sound_dir = app_conf().chime.sound_dir;
+    let user_sounds = audio.user_sounds.clone();
+    use_effect(use_reactive(&sound_dir, move |sound_dir| {
+        if let Some(task) = task.write().take() {
+            task.cancel();
+        }
+        let user_sounds = user_sounds.clone();
+        task.set(Some(spawn(async move {
+            *user_sounds.borrow_mut() = load_user_sounds(sound_dir).await;
+        })));
+    }));
+
+    audio
+}
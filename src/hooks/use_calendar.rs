@@ -0,0 +1,134 @@
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use freya::prelude::*;
+use tokio::time::sleep;
+
+use crate::{
+    app_config::AppConfig,
+    calendar::{next_event, parse_calendar, CalendarConfig, CalendarEvent, UpcomingEvent},
+    event_hooks::{HookEvent, UseEventHooks},
+    hooks::{UseAudio, UseFlash},
+};
+
+// 检查下一个事件和提醒的间隔
+const CHECK_INTERVAL: u64 = 1000;
+// 每隔几次检查一下文件有没有变化
+const RELOAD_EVERY: u32 = 5;
+// 没有找到事件时过一段时间再找，单位是秒
+const IDLE_RECHECK: i64 = 60;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct UseCalendar {
+    next: Signal<Option<UpcomingEvent>>,
+}
+
+impl UseCalendar {
+    pub fn next_event(&self) -> Option<UpcomingEvent> {
+        (self.next)()
+    }
+}
+
+/// 读取配置中的 .ics 文件，找到接下来的事件，并在事件开始之前提醒
+pub fn use_calendar(
+    app_conf: Signal<AppConfig>,
+    event_hooks: UseEventHooks,
+    mut flash: UseFlash,
+    audio: UseAudio,
+) -> UseCalendar {
+    let mut next = use_signal(|| None as Option<UpcomingEvent>);
+
+    use_effect(move || {
+        let audio = audio.clone();
+        spawn(async move {
+            let mut last_config = None as Option<CalendarConfig>;
+            let mut files = vec![] as Vec<(String, Option<SystemTime>)>;
+            let mut events = vec![] as Vec<CalendarEvent>;
+            let mut recheck_at = Utc::now();
+            let mut alerted = None as Option<(String, DateTime<Utc>)>;
+
+            for tick in 0.. {
+                let config = app_conf.peek().calendar.clone();
+                let config_changed = last_config.as_ref() != Some(&config);
+                let mut dirty = config_changed;
+
+                if config_changed || tick % RELOAD_EVERY == 0 {
+                    let modified = modified_times(&config.files).await;
+                    if modified != files {
+                        events = load_events(&config.files).await;
+                        files = modified;
+                        dirty = true;
+                    }
+                }
+                last_config = Some(config.clone());
+
+                let now = Utc::now();
+                if dirty || now >= recheck_at {
+                    let horizon = now + TimeDelta::days(config.lookahead_days as i64);
+                    let upcoming = next_event(&events, now, horizon);
+                    recheck_at = match &upcoming {
+                        Some(event) => event.start,
+                        None => now + TimeDelta::seconds(IDLE_RECHECK),
+                    };
+                    if *next.peek() != upcoming {
+                        next.set(upcoming);
+                    }
+                }
+
+                let upcoming = next.peek().clone();
+                if let Some(event) = upcoming.filter(|_| config.alert_minutes > 0) {
+                    let key = (event.uid.clone(), event.start);
+                    let alert_at = event.start - TimeDelta::minutes(config.alert_minutes as i64);
+                    if now >= alert_at && alerted.as_ref() != Some(&key) {
+                        alerted = Some(key);
+
+                        let start = event
+                            .start
+                            .with_timezone(&Local)
+                            .format("%H:%M")
+                            .to_string();
+                        log::info!("calendar: {} at {start}", event.title);
+                        event_hooks.fire(
+                            HookEvent::OnAlarm,
+                            vec![("title", event.title.clone()), ("start", start)],
+                        );
+
+                        let app_conf = app_conf.peek();
+                        if !app_conf.muted {
+                            audio.play(&["beep".to_string()], app_conf.chime.volume);
+                        }
+
+                        flash.flash();
+                    }
+                }
+
+                sleep(Duration::from_millis(CHECK_INTERVAL)).await;
+            }
+        });
+    });
+
+    UseCalendar { next }
+}
+
+async fn modified_times(files: &[String]) -> Vec<(String, Option<SystemTime>)> {
+    let mut modified = vec![];
+    for file in files {
+        let time = tokio::fs::metadata(file)
+            .await
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        modified.push((file.clone(), time));
+    }
+    modified
+}
+
+async fn load_events(files: &[String]) -> Vec<CalendarEvent> {
+    let mut events = vec![];
+    for file in files {
+        match tokio::fs::read_to_string(file).await {
+            Ok(text) => events.extend(parse_calendar(&text)),
+            Err(e) => log::warn!("calendar: failed to read {file}: {e}"),
+        }
+    }
+    events
+}
@@ -0,0 +1,32 @@
+use std::{cell::RefCell, rc::Rc};
+
+use freya::prelude::*;
+
+use crate::{
+    app_config::AppConfig,
+    chime::{ChimeScheduler, ChimeStyle, LocalTime},
+    hooks::UseAudio,
+    times::UseCurrentTime,
+};
+
+/// 每分钟检查一次是否需要报时，同一分钟内只会报时一次
+///
+/// 报时不依赖界面的重新渲染，需要在 `MyApp` 中调用，打开设置时也不会停止
+pub fn use_chime(app_conf: Signal<AppConfig>, audio: UseAudio, current_time: UseCurrentTime) {
+    let scheduler = use_hook(|| {
+        Rc::new(RefCell::new(ChimeScheduler::new(
+            Box::new(LocalTime),
+            Box::new(audio),
+        )))
+    });
+
+    use_effect(move || {
+        // 订阅共用时钟的分钟
+        let _ = (current_time.minute)();
+        let config = app_conf.peek().chime.clone();
+        if config.style == ChimeStyle::Off || app_conf.peek().muted {
+            return;
+        }
+        scheduler.borrow_mut().tick(&config);
+    });
+}
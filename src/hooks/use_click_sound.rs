@@ -0,0 +1,45 @@
+use std::{cell::RefCell, rc::Rc, time::Instant};
+
+use freya::prelude::*;
+
+use crate::{
+    app_config::AppConfig,
+    click_sound::{ClickLimiter, DigitGroup},
+    hooks::UseAudio,
+};
+
+#[derive(Clone)]
+pub struct UseClickSound {
+    app_conf: Signal<AppConfig>,
+    audio: UseAudio,
+    limiter: Rc<RefCell<ClickLimiter>>,
+}
+
+impl UseClickSound {
+    /// 数字开始翻动时调用
+    pub fn click(&self, group: DigitGroup) {
+        let app_conf = self.app_conf.peek();
+        let config = &app_conf.click_sound;
+        if app_conf.muted || !config.enabled || !config.groups.contains(&group) {
+            return;
+        }
+
+        if !self.limiter.borrow_mut().try_click(Instant::now()) {
+            return;
+        }
+        self.audio.play(&["click".to_string()], config.volume);
+    }
+}
+
+/// 为下面的数字提供翻页声音
+pub fn use_click_sound_provider(app_conf: Signal<AppConfig>, audio: UseAudio) {
+    use_context_provider(|| UseClickSound {
+        app_conf,
+        audio,
+        limiter: Rc::new(RefCell::new(ClickLimiter::default())),
+    });
+}
+
+pub fn use_click_sound() -> UseClickSound {
+    consume_context::<UseClickSound>()
+}
@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use chrono::{Local, TimeDelta};
+use freya::prelude::*;
+use tokio::time::sleep;
+
+use crate::{
+    app_config::{AppConfig, ClockMode},
+    countdown::{countdown_digits, CountdownFinish, ALARM_LABEL, TIMER_LABEL},
+    event_hooks::{HookEvent, UseEventHooks},
+    hooks::{UseFlash, UseIdleFade},
+};
+
+const CHECK_INTERVAL: u64 = 1000;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct UseCountdown {
+    app_conf: Signal<AppConfig>,
+    remaining: Signal<Option<TimeDelta>>,
+}
+
+impl UseCountdown {
+    /// 倒计时模式下显示的天、小时和分钟，不在倒计时模式时返回 None
+    pub fn digits(&self) -> Option<(u32, u32, u32)> {
+        (self.remaining)().map(countdown_digits)
+    }
+
+    /// 当前目标的名称
+    pub fn label(&self) -> Option<String> {
+        let app_conf = self.app_conf.read();
+        if app_conf.mode != ClockMode::Countdown {
+            return None;
+        }
+        app_conf
+            .countdown
+            .active_target()
+            .map(|target| target.label.clone())
+            .filter(|label| !label.is_empty())
+    }
+
+    /// 在时钟和倒计时之间切换，没有倒计时目标时不切换，和右键菜单一致
+    pub fn toggle_mode(&mut self) {
+        if self.app_conf.peek().countdown.targets.is_empty() {
+            return;
+        }
+        let mode = self.app_conf.peek().mode.toggle();
+        self.app_conf.write().mode = mode;
+        self.remaining.set(remaining(&self.app_conf.peek()));
+    }
+
+    /// 没有计时器时按 `countdown.timer_seconds` 启动一个并显示倒计时，已经有计时器时取消它
+    pub fn toggle_timer(&mut self) {
+        if self.remove_target(TIMER_LABEL) {
+            return;
+        }
+        let seconds = self.app_conf.peek().countdown.timer_seconds;
+        let Some(time) = TimeDelta::try_seconds(seconds as i64)
+            .and_then(|seconds| Local::now().checked_add_signed(seconds))
+        else {
+            return;
+        };
+        let mut app_conf = self.app_conf.write();
+        app_conf.countdown.set_target(TIMER_LABEL, time);
+        app_conf.mode = ClockMode::Countdown;
+        drop(app_conf);
+        self.remaining.set(remaining(&self.app_conf.peek()));
+    }
+
+    /// 取消控制命令设置的闹钟
+    pub fn clear_alarm(&mut self) {
+        self.remove_target(ALARM_LABEL);
+    }
+
+    // 删除目标之后没有其他目标时切换回时钟，返回是否有这个目标
+    fn remove_target(&mut self, label: &str) -> bool {
+        let mut app_conf = self.app_conf.write();
+        if !app_conf.countdown.remove_target(label) {
+            return false;
+        }
+        if app_conf.countdown.targets.is_empty() {
+            app_conf.mode = ClockMode::Clock;
+        }
+        drop(app_conf);
+        self.remaining.set(remaining(&self.app_conf.peek()));
+        true
+    }
+
+    /// 切换到下一个目标
+    pub fn next_target(&mut self) {
+        self.app_conf.write().countdown.next_target();
+        self.remaining.set(remaining(&self.app_conf.peek()));
+    }
+}
+
+// 不在倒计时模式或者没有有效的目标时为 None
+fn remaining(app_conf: &AppConfig) -> Option<TimeDelta> {
+    if app_conf.mode != ClockMode::Countdown {
+        return None;
+    }
+    let target = app_conf.countdown.active_target()?.target_time()?;
+    Some(target - Local::now())
+}
+
+/// 倒计时模式下每秒计算到目标时间的剩余时间，到达目标时运行 `on_countdown_done` 命令
+///
+/// 到达的是计时器或者闹钟时，还会运行 `on_timer_done` 或者 `on_alarm` 命令。
+pub fn use_countdown(
+    app_conf: Signal<AppConfig>,
+    event_hooks: UseEventHooks,
+    mut flash: UseFlash,
+    mut idle_fade: UseIdleFade,
+) -> UseCountdown {
+    let mut remaining_time = use_signal(|| remaining(&app_conf.peek()));
+
+    use_effect(move || {
+        spawn(async move {
+            // 正在倒计时的目标，启动时已经过去的目标不算结束
+            let mut armed = None as Option<String>;
+            loop {
+                let remaining = remaining(&app_conf.peek());
+                if *remaining_time.peek() != remaining {
+                    remaining_time.set(remaining);
+                }
+
+                let target = app_conf.peek().countdown.active_target().cloned();
+                if let (Some(target), Some(remaining)) = (target, remaining) {
+                    if remaining > TimeDelta::zero() {
+                        armed = Some(target.time.clone());
+                    } else if armed.as_ref() == Some(&target.time) {
+                        armed = None;
+                        log::info!("countdown {} finished", target.label);
+                        // 不闪烁的时候也要让渐隐的窗口显示出来
+                        idle_fade.wake();
+                        let vars = vec![("label", target.label.clone()), ("target", target.time)];
+                        event_hooks.fire(HookEvent::OnCountdownDone, vars.clone());
+                        // 计时器和闹钟另外触发自己的事件
+                        let event = match target.label.as_str() {
+                            TIMER_LABEL => Some(HookEvent::OnTimerDone),
+                            ALARM_LABEL => Some(HookEvent::OnAlarm),
+                            _ => None,
+                        };
+                        if let Some(event) = event {
+                            event_hooks.fire(event, vars);
+                        }
+                        match target.on_finish {
+                            CountdownFinish::Hold => {}
+                            CountdownFinish::Flash => flash.flash(),
+                            CountdownFinish::Clock => {
+                                app_conf.write().mode = ClockMode::Clock;
+                                remaining_time.set(None);
+                            }
+                        }
+                    }
+                }
+
+                sleep(Duration::from_millis(CHECK_INTERVAL)).await;
+            }
+        });
+    });
+
+    UseCountdown {
+        app_conf,
+        remaining: remaining_time,
+    }
+}
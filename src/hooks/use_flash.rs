@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use freya::prelude::*;
+use tokio::time::sleep;
+
+use crate::hooks::UseIdleFade;
+
+// 闪烁的次数和每次变暗、变亮的时长
+const FLASH_TIMES: u32 = 10;
+const FLASH_INTERVAL: u64 = 500;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct UseFlash {
+    dimmed: Signal<bool>,
+    task: Signal<Option<Task>>,
+    idle_fade: UseIdleFade,
+}
+
+impl UseFlash {
+    /// 窗口一明一暗地闪烁几次，用于日历提醒和倒计时结束
+    pub fn flash(&mut self) {
+        // 窗口渐隐之后闪烁看不清楚
+        self.idle_fade.wake();
+        if let Some(task) = self.task.write().take() {
+            task.cancel();
+        }
+        let mut dimmed = self.dimmed;
+        let task = spawn(async move {
+            for _ in 0..FLASH_TIMES {
+                dimmed.set(true);
+                sleep(Duration::from_millis(FLASH_INTERVAL)).await;
+                dimmed.set(false);
+                sleep(Duration::from_millis(FLASH_INTERVAL)).await;
+            }
+        });
+        self.task.set(Some(task));
+    }
+
+    /// 闪烁中变暗的时候返回 true
+    pub fn is_dimmed(&self) -> bool {
+        (self.dimmed)()
+    }
+}
+
+pub fn use_flash(idle_fade: UseIdleFade) -> UseFlash {
+    UseFlash {
+        dimmed: use_signal(|| false),
+        task: use_signal(|| None),
+        idle_fade,
+    }
+}
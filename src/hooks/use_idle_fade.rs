@@ -0,0 +1,108 @@
+use std::time::{Duration, Instant};
+
+use freya::prelude::*;
+use tokio::time::sleep;
+
+use crate::app_config::AppConfig;
+
+const FRAME_TIME: u64 = 16;
+const FADE_DURATION: u64 = 600;
+// 检查是否已经空闲的间隔
+const CHECK_INTERVAL: u64 = 500;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct UseIdleFade {
+    app_conf: Signal<AppConfig>,
+    opacity: Signal<f32>,
+    target: Signal<f32>,
+    hovered: Signal<bool>,
+    last_active: Signal<Instant>,
+    task: Signal<Option<Task>>,
+}
+
+impl UseIdleFade {
+    /// 当前整个窗口的不透明度
+    pub fn opacity(&self) -> f32 {
+        (self.opacity)()
+    }
+
+    /// 有操作或者事件发生时恢复到正常的不透明度，并重新开始计算空闲时间
+    pub fn wake(&mut self) {
+        self.last_active.set(Instant::now());
+        let opacity = self.app_conf.peek().opacity;
+        self.fade_to(opacity);
+    }
+
+    pub fn set_hovered(&mut self, hovered: bool) {
+        self.hovered.set(hovered);
+        self.wake();
+    }
+
+    fn fade_to(&mut self, target: f32) {
+        if *self.target.peek() == target {
+            return;
+        }
+        self.target.set(target);
+        if let Some(task) = self.task.write().take() {
+            task.cancel();
+        }
+
+        let mut opacity = self.opacity;
+        let from = *opacity.peek();
+        let steps = (FADE_DURATION / FRAME_TIME).max(1);
+        let task = spawn(async move {
+            for step in 1..=steps {
+                let progress = step as f32 / steps as f32;
+                // ease out
+                let progress = 1.0 - (1.0 - progress).powi(3);
+                opacity.set(from + (target - from) * progress);
+                sleep(Duration::from_millis(FRAME_TIME)).await;
+            }
+        });
+        self.task.set(Some(task));
+    }
+
+    fn is_idle(&self) -> bool {
+        let idle_timeout = self.app_conf.peek().idle_timeout;
+        idle_timeout > 0
+            && !*self.hovered.peek()
+            && self.last_active.peek().elapsed() >= Duration::from_secs(idle_timeout)
+    }
+}
+
+/// 鼠标离开窗口 `idle_timeout` 秒之后，整个窗口渐隐到 `idle_opacity`
+pub fn use_idle_fade(app_conf: Signal<AppConfig>) -> UseIdleFade {
+    let opacity = app_conf.peek().opacity;
+    let mut idle_fade = UseIdleFade {
+        app_conf,
+        opacity: use_signal(|| opacity),
+        target: use_signal(|| opacity),
+        hovered: use_signal(|| false),
+        last_active: use_signal(Instant::now),
+        task: use_signal(|| None),
+    };
+
+    // 修改了不透明度的配置之后按新的值重新渐变
+    let AppConfig {
+        opacity,
+        idle_opacity,
+        ..
+    } = app_conf();
+    use_effect(use_reactive((&opacity, &idle_opacity), move |_| {
+        idle_fade.wake();
+    }));
+
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                sleep(Duration::from_millis(CHECK_INTERVAL)).await;
+                if idle_fade.is_idle() {
+                    let idle_opacity = idle_fade.app_conf.peek().idle_opacity;
+                    idle_fade.fade_to(idle_opacity);
+                }
+            }
+        });
+    });
+
+    idle_fade
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use freya::prelude::*;
+use tokio::{sync::mpsc::unbounded_channel, time::sleep};
+
+use crate::placement::MonitorIdentity;
+
+// 显示器可能随时插拔，隔一段时间重新获取一次
+const REFRESH_INTERVAL: u64 = 2000;
+
+/// 所有显示器的位置和大小，单位是物理像素，第一个是主显示器
+pub fn use_monitors() -> Signal<Vec<Area>> {
+    let platform = use_platform();
+    let mut monitors = use_signal(Vec::<Area>::new);
+
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                let new_monitors = fetch_monitors(platform).await;
+                if *monitors.peek() != new_monitors {
+                    monitors.set(new_monitors);
+                }
+                sleep(Duration::from_millis(REFRESH_INTERVAL)).await;
+            }
+        });
+    });
+
+    monitors
+}
+
+/// 窗口当前所在的显示器
+pub async fn fetch_current_monitor(platform: UsePlatform) -> Option<MonitorIdentity> {
+    let (tx, mut rx) = unbounded_channel::<Option<MonitorIdentity>>();
+    platform.with_window(move |window| {
+        let monitor = window.current_monitor().map(|monitor| MonitorIdentity {
+            name: monitor.name().unwrap_or_default(),
+            scale_factor: monitor.scale_factor() as f32,
+        });
+        let _ = tx.send(monitor);
+    });
+    rx.recv().await.flatten()
+}
+
+async fn fetch_monitors(platform: UsePlatform) -> Vec<Area> {
+    let (tx, mut rx) = unbounded_channel::<Vec<Area>>();
+    platform.with_window(move |window| {
+        let primary = window.primary_monitor();
+        let mut monitors = window.available_monitors().collect::<Vec<_>>();
+        // 主显示器排在最前面
+        monitors.sort_by_key(|monitor| Some(monitor) != primary.as_ref());
+
+        let monitors = monitors
+            .into_iter()
+            .map(|monitor| {
+                let position = monitor.position();
+                let size = monitor.size();
+                Area::new(
+                    Point2D::new(position.x as f32, position.y as f32),
+                    Size2D::new(size.width as f32, size.height as f32),
+                )
+            })
+            .collect();
+        let _ = tx.send(monitors);
+    });
+    rx.recv().await.unwrap_or_default()
+}
@@ -0,0 +1,143 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use freya::prelude::*;
+use tokio::time::sleep;
+
+use crate::{process, snap::SnapLines};
+
+// 其他时钟窗口的位置隔一段时间刷新一次，本窗口的记录也同时重新写入
+const REFRESH_INTERVAL: u64 = 2000;
+// 超过这么久没有更新的记录来自已经退出的窗口
+const STALE_AFTER: u64 = 5 * REFRESH_INTERVAL;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct UseSnap {
+    monitors: Signal<Vec<Area>>,
+    other_windows: Signal<Vec<Area>>,
+    bypass: Signal<bool>,
+    // 本窗口最后一次记录的位置
+    published: Signal<Option<Area>>,
+}
+
+impl UseSnap {
+    pub fn lines(&self) -> SnapLines {
+        SnapLines::from_areas(self.monitors.peek().iter().chain(self.other_windows.peek().iter()))
+    }
+
+    /// 按住修饰键的时候不吸附
+    pub fn is_bypassed(&self) -> bool {
+        *self.bypass.peek()
+    }
+
+    pub fn set_bypass(&mut self, bypass: bool) {
+        self.bypass.set(bypass);
+    }
+
+    /// 记录本窗口的位置，供其他时钟窗口吸附
+    pub fn publish_window(&mut self, window: Area) {
+        self.published.set(Some(window));
+        write_own_window(window);
+    }
+}
+
+pub fn use_snap(monitors: Signal<Vec<Area>>) -> UseSnap {
+    let mut other_windows = use_signal(Vec::<Area>::new);
+    let bypass = use_signal(|| false);
+    let published = use_signal(|| None as Option<Area>);
+
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                // 定期重新写入，其他窗口根据修改时间判断记录是否还有效
+                if let Some(window) = *published.peek() {
+                    write_own_window(window);
+                }
+                other_windows.set(read_other_windows());
+                sleep(Duration::from_millis(REFRESH_INTERVAL)).await;
+            }
+        });
+    });
+
+    use_drop(|| {
+        let _ = std::fs::remove_file(own_window_path());
+    });
+
+    UseSnap {
+        monitors,
+        other_windows,
+        bypass,
+        published,
+    }
+}
+
+fn windows_dir() -> PathBuf {
+    std::env::temp_dir().join("flip_clock_windows")
+}
+
+fn own_window_path() -> PathBuf {
+    windows_dir().join(std::process::id().to_string())
+}
+
+fn write_own_window(window: Area) {
+    let _ = std::fs::create_dir_all(windows_dir());
+    let _ = std::fs::write(
+        own_window_path(),
+        format!(
+            "{} {} {} {}",
+            window.origin.x, window.origin.y, window.size.width, window.size.height
+        ),
+    );
+}
+
+fn read_other_windows() -> Vec<Area> {
+    let own_window_path = own_window_path();
+    let Ok(entries) = std::fs::read_dir(windows_dir()) else {
+        return vec![];
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| *path != own_window_path)
+        .filter(|path| is_live_window(path))
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .filter_map(|content| {
+            let values = content
+                .split_whitespace()
+                .map(|value| value.parse::<f32>().ok())
+                .collect::<Option<Vec<_>>>()?;
+            let [x, y, width, height] = values[..] else {
+                return None;
+            };
+            Some(Area::new(Point2D::new(x, y), Size2D::new(width, height)))
+        })
+        .collect()
+}
+
+// 文件名是窗口所在进程的进程号，进程已经退出的记录顺便删除
+fn is_live_window(path: &Path) -> bool {
+    let pid = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.parse::<u32>().ok());
+    let Some(pid) = pid else {
+        return false;
+    };
+    if !process::is_alive(pid) {
+        let _ = std::fs::remove_file(path);
+        return false;
+    }
+
+    let age = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(|modified| {
+            SystemTime::now()
+                .duration_since(modified)
+                .unwrap_or_default()
+        });
+    age.is_some_and(|age| age < Duration::from_millis(STALE_AFTER))
+}
@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use freya::prelude::*;
+use tokio::time::sleep;
+
+const FRAME_TIME: u64 = 16;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct UseWindowAnimation {
+    platform: UsePlatform,
+    task: Signal<Option<Task>>,
+}
+
+impl UseWindowAnimation {
+    /// 在 `duration` 毫秒内把窗口平滑地调整到新的大小和位置
+    pub fn animate_to(&mut self, size: Size2D, position: Point2D, duration: u64) {
+        if let Some(task) = self.task.write().take() {
+            task.cancel();
+        }
+
+        let platform = self.platform;
+        let PlatformInformation {
+            window_size: from_size,
+            window_position: from_position,
+            ..
+        } = platform.info();
+        let steps = (duration / FRAME_TIME).max(1);
+
+        let task = spawn(async move {
+            for step in 1..=steps {
+                let progress = step as f32 / steps as f32;
+                // ease out
+                let progress = 1.0 - (1.0 - progress).powi(3);
+                platform.set_window_size_and_position(
+                    from_size.lerp(size, progress),
+                    from_position.lerp(position, progress),
+                );
+                sleep(Duration::from_millis(FRAME_TIME)).await;
+            }
+        });
+        self.task.set(Some(task));
+    }
+}
+
+pub fn use_window_animation() -> UseWindowAnimation {
+    let platform = use_platform();
+    let task = use_signal(|| None);
+    UseWindowAnimation { platform, task }
+}
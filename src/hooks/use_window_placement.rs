@@ -0,0 +1,77 @@
+use freya::prelude::*;
+
+use crate::placement::{clamp_on_screen, monitor_of, preset_position, Anchor, PositionPreset};
+
+#[derive(Clone, Copy)]
+pub struct UseWindowPlacement {
+    platform: UsePlatform,
+    monitors: Signal<Vec<Area>>,
+    // 最近一次自动摆放的位置
+    placed: Signal<Option<Point2D>>,
+}
+
+impl UseWindowPlacement {
+    /// 有预设位置时移动到预设位置，否则只在窗口跑到屏幕外时把它移回来
+    pub fn place(&mut self, preset: Option<PositionPreset>) {
+        let monitors = self.monitors.peek();
+        // 还没有获取到显示器信息
+        if monitors.is_empty() {
+            return;
+        }
+
+        let PlatformInformation {
+            window_size,
+            window_position,
+            window_scale_factor,
+            ..
+        } = self.platform.info();
+        let position = match preset {
+            Some(preset) => preset_position(preset, &monitors, window_size, window_scale_factor),
+            None => clamp_on_screen(Area::new(window_position, window_size), &monitors),
+        };
+        if position != window_position {
+            self.placed.set(Some(position));
+            self.platform.set_window_size_and_position(window_size, position);
+        }
+    }
+
+    /// 窗口是否停在自动摆放的位置，不是的话说明用户拖动过窗口
+    pub fn is_placed(&self, position: Point2D) -> bool {
+        *self.placed.peek() == Some(position)
+    }
+
+    /// 在窗口当前所在的显示器上切换到下一个预设位置
+    pub fn next_preset(&self, current: Option<PositionPreset>) -> PositionPreset {
+        let PlatformInformation {
+            window_size,
+            window_position,
+            ..
+        } = self.platform.info();
+        let monitor = monitor_of(Area::new(window_position, window_size), &self.monitors.peek());
+        PositionPreset {
+            anchor: current.map(|preset| preset.anchor.next()).unwrap_or(Anchor::ALL[0]),
+            monitor: monitor.unwrap_or_default(),
+            margin: current.map(|preset| preset.margin).unwrap_or_default(),
+        }
+    }
+}
+
+pub fn use_window_placement(
+    monitors: Signal<Vec<Area>>,
+    preset: Option<PositionPreset>,
+) -> UseWindowPlacement {
+    let platform = use_platform();
+    let placed = use_signal(|| None);
+    let mut placement = UseWindowPlacement {
+        platform,
+        monitors,
+        placed,
+    };
+
+    // 显示器插拔、分辨率变化或者预设位置变化时重新摆放窗口
+    use_effect(use_reactive((&monitors(), &preset), move |(_, preset)| {
+        placement.place(preset);
+    }));
+
+    placement
+}
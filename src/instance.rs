@@ -0,0 +1,238 @@
+use std::{
+    collections::hash_map::RandomState,
+    fs::{File, OpenOptions},
+    hash::{BuildHasher, Hasher},
+    io::Write,
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::PathBuf,
+    sync::OnceLock,
+    time::{Duration, SystemTime},
+};
+
+use freya::prelude::*;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, BufReader},
+    time::sleep,
+};
+
+use crate::{
+    components::AppConfigContext,
+    control::{exchange, serve_connection, Request, Response, ACCEPT_RETRY_INTERVAL},
+};
+
+// 持有锁的文件，进程退出时系统自动释放锁
+static LOCK_FILE: OnceLock<File> = OnceLock::new();
+// 主实例用来接收转发命令的端口，启动窗口之后交给 use_instance_server
+static LISTENER: OnceLock<TcpListener> = OnceLock::new();
+// 写在锁文件中的令牌，连接转发端口时第一行必须是这个令牌
+static TOKEN: OnceLock<String> = OnceLock::new();
+
+// 主实例可能刚拿到锁还没有写入内容，读不到内容或者连不上时重试
+const READ_RETRIES: u32 = 10;
+const READ_RETRY_INTERVAL: u64 = 50;
+const FORWARD_TIMEOUT: u64 = 3;
+// 令牌那一行最多读取的长度
+const MAX_TOKEN_SIZE: u64 = 128;
+
+pub enum Instance {
+    // 当前进程持有锁
+    Primary,
+    // 已经有时钟在运行，命令行参数已经转发给它
+    Forwarded(Response),
+}
+
+/// 锁文件中记录的主实例
+struct LockInfo {
+    port: u16,
+    token: String,
+}
+
+/// 锁文件的路径，内容是持有锁的进程号、接收转发命令的端口和令牌
+fn lock_path() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("flip_clock.lock")
+}
+
+/// 获取单实例锁，已经有时钟在运行时把 `args` 转发给它
+///
+/// 锁由系统在进程退出时释放，崩溃或被杀死的时钟不会留下仍然有效的锁，锁文件本身不删除。
+/// 锁被占用但是连不上持有锁的时钟时返回错误。
+pub fn acquire(args: Vec<String>) -> std::io::Result<Instance> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let token = random_token();
+    let content = format!(
+        "{} {} {token}",
+        std::process::id(),
+        listener.local_addr()?.port()
+    );
+
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create(true);
+    // 令牌只给当前用户读取
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut lock_file = options.open(lock_path())?;
+    if try_lock(&lock_file)? {
+        // 拿到锁之后才覆盖上一个主实例留下的内容
+        lock_file.set_len(0)?;
+        lock_file.write_all(content.as_bytes())?;
+        let _ = LOCK_FILE.set(lock_file);
+        let _ = LISTENER.set(listener);
+        let _ = TOKEN.set(token);
+        return Ok(Instance::Primary);
+    }
+
+    let mut retries = 0;
+    let (mut stream, token) = loop {
+        match connect() {
+            Ok(connection) => break connection,
+            Err(e) if retries == READ_RETRIES => return Err(e),
+            Err(_) => {
+                retries += 1;
+                std::thread::sleep(Duration::from_millis(READ_RETRY_INTERVAL));
+            }
+        }
+    };
+    stream.set_read_timeout(Some(Duration::from_secs(FORWARD_TIMEOUT)))?;
+    stream.write_all(format!("{token}\n").as_bytes())?;
+    let response = exchange(stream, &Request::Forward { args })?;
+    Ok(Instance::Forwarded(response))
+}
+
+// 连接锁文件中记录的主实例，返回连接和令牌
+fn connect() -> std::io::Result<(TcpStream, String)> {
+    let lock = read_lock()?;
+    let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, lock.port))?;
+    Ok((stream, lock.token))
+}
+
+fn read_lock() -> std::io::Result<LockInfo> {
+    let content = std::fs::read_to_string(lock_path())?;
+    let mut parts = content.split_whitespace();
+    // 第一项是进程号，只用于排查问题
+    parts
+        .nth(1)
+        .and_then(|port| port.parse().ok())
+        .zip(parts.next())
+        .map(|(port, token)| LockInfo {
+            port,
+            token: token.to_string(),
+        })
+        .ok_or_else(|| {
+            let error = format!("incomplete lock file {}", lock_path().display());
+            std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+        })
+}
+
+/// 不等待地获取排它锁，锁已经被其他进程持有时返回 false
+#[cfg(unix)]
+fn try_lock(file: &File) -> std::io::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(true);
+    }
+    let error = std::io::Error::last_os_error();
+    if error.raw_os_error() == Some(libc::EWOULDBLOCK) {
+        Ok(false)
+    } else {
+        Err(error)
+    }
+}
+
+/// 不等待地获取排它锁，锁已经被其他进程持有时返回 false
+#[cfg(windows)]
+fn try_lock(file: &File) -> std::io::Result<bool> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::{
+        Foundation::ERROR_LOCK_VIOLATION,
+        Storage::FileSystem::{LockFileEx, LOCKFILE_EXCLUSIVE_LOCK, LOCKFILE_FAIL_IMMEDIATELY},
+        System::IO::OVERLAPPED,
+    };
+
+    // Windows 的锁是强制的，只锁住远在内容之后的一个字节，其他实例仍然可以读取内容
+    let ok = unsafe {
+        let mut overlapped: OVERLAPPED = std::mem::zeroed();
+        overlapped.Anonymous.Anonymous.Offset = u32::MAX;
+        overlapped.Anonymous.Anonymous.OffsetHigh = i32::MAX as u32;
+        LockFileEx(
+            file.as_raw_handle() as _,
+            LOCKFILE_EXCLUSIVE_LOCK | LOCKFILE_FAIL_IMMEDIATELY,
+            0,
+            1,
+            0,
+            &mut overlapped,
+        )
+    };
+    if ok != 0 {
+        return Ok(true);
+    }
+    let error = std::io::Error::last_os_error();
+    if error.raw_os_error() == Some(ERROR_LOCK_VIOLATION as i32) {
+        Ok(false)
+    } else {
+        Err(error)
+    }
+}
+
+// 每个 RandomState 使用不同的随机密钥，两次哈希拼成 128 位的令牌
+fn random_token() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    (0..2)
+        .map(|_| {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u128(nanos);
+            hasher.write_u32(std::process::id());
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+/// 接收其他实例转发过来的命令
+pub fn use_instance_server(app_config_context: AppConfigContext) {
+    let platform = use_platform();
+
+    use_effect(move || {
+        let Some(listener) = LISTENER
+            .get()
+            .and_then(|listener| listener.try_clone().ok())
+        else {
+            return;
+        };
+        spawn(async move {
+            let _ = listener.set_nonblocking(true);
+            let Ok(listener) = tokio::net::TcpListener::from_std(listener) else {
+                return;
+            };
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        log::warn!("failed to accept forwarded connection: {e}");
+                        sleep(Duration::from_millis(ACCEPT_RETRY_INTERVAL)).await;
+                        continue;
+                    }
+                };
+                spawn(async move {
+                    // 第一行是锁文件中的令牌，之后和控制 socket 一样逐行处理 JSON 命令
+                    let mut stream = BufReader::new(stream);
+                    let mut token = String::new();
+                    let read = (&mut stream)
+                        .take(MAX_TOKEN_SIZE)
+                        .read_line(&mut token)
+                        .await;
+                    if read.is_err() || Some(token.trim()) != TOKEN.get().map(String::as_str) {
+                        log::warn!("rejected a forwarded connection with an invalid token");
+                        return;
+                    }
+                    serve_connection(stream, app_config_context, platform).await;
+                });
+            }
+        });
+    });
+}
@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+
+use freya::prelude::{Key, Modifiers};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAction {
+    ToggleLock,
+    ToggleGhost,
+    CycleWindowLevel,
+    NextProfile,
+    ToggleCountdown,
+    StartStopTimer,
+    OpenSettings,
+    ShowHelp,
+    Quit,
+    NudgeLeft,
+    NudgeRight,
+    NudgeUp,
+    NudgeDown,
+    Grow,
+    Shrink,
+}
+
+impl KeyAction {
+    pub fn description(&self) -> &'static str {
+        match self {
+            KeyAction::ToggleLock => "Lock / unlock",
+            KeyAction::ToggleGhost => "Ghost mode (click through)",
+            KeyAction::CycleWindowLevel => "Cycle window level",
+            KeyAction::NextProfile => "Next profile",
+            KeyAction::ToggleCountdown => "Clock / countdown",
+            KeyAction::StartStopTimer => "Start / stop timer",
+            KeyAction::OpenSettings => "Settings",
+            KeyAction::ShowHelp => "Show shortcuts",
+            KeyAction::Quit => "Quit",
+            KeyAction::NudgeLeft => "Move left",
+            KeyAction::NudgeRight => "Move right",
+            KeyAction::NudgeUp => "Move up",
+            KeyAction::NudgeDown => "Move down",
+            KeyAction::Grow => "Bigger",
+            KeyAction::Shrink => "Smaller",
+        }
+    }
+}
+
+pub fn default_keymap() -> BTreeMap<KeyAction, String> {
+    [
+        (KeyAction::ToggleLock, "L"),
+        (KeyAction::ToggleGhost, "G"),
+        (KeyAction::CycleWindowLevel, "T"),
+        (KeyAction::NextProfile, "P"),
+        (KeyAction::ToggleCountdown, "C"),
+        (KeyAction::StartStopTimer, "Space"),
+        (KeyAction::OpenSettings, "S"),
+        (KeyAction::ShowHelp, "?"),
+        (KeyAction::Quit, "Q"),
+        (KeyAction::NudgeLeft, "ArrowLeft"),
+        (KeyAction::NudgeRight, "ArrowRight"),
+        (KeyAction::NudgeUp, "ArrowUp"),
+        (KeyAction::NudgeDown, "ArrowDown"),
+        (KeyAction::Grow, "+"),
+        (KeyAction::Shrink, "-"),
+    ]
+    .into_iter()
+    .map(|(action, key)| (action, key.to_string()))
+    .collect()
+}
+
+/// 默认快捷键和配置中的快捷键合并之后的结果，配置中的空字符串表示取消绑定
+pub fn effective_keymap(custom: &BTreeMap<KeyAction, String>) -> BTreeMap<KeyAction, String> {
+    let mut keymap = default_keymap();
+    keymap.extend(custom.clone());
+    keymap.retain(|_, key| !key.trim().is_empty());
+    keymap.values_mut().for_each(|key| *key = normalize_binding(key));
+    keymap
+}
+
+/// 绑定到同一个按键的多个动作
+pub fn find_conflicts(keymap: &BTreeMap<KeyAction, String>) -> Vec<(String, Vec<KeyAction>)> {
+    let mut actions_by_key = BTreeMap::<String, Vec<KeyAction>>::new();
+    for (action, key) in keymap {
+        actions_by_key.entry(key.clone()).or_default().push(*action);
+    }
+    actions_by_key
+        .into_iter()
+        .filter(|(_, actions)| actions.len() > 1)
+        .collect()
+}
+
+pub fn find_action(
+    keymap: &BTreeMap<KeyAction, String>,
+    key: &Key,
+    modifiers: Modifiers,
+) -> Option<KeyAction> {
+    let binding = binding_name(key, modifiers);
+    keymap
+        .iter()
+        .find(|(_, key)| **key == binding)
+        .map(|(action, _)| *action)
+}
+
+/// 把按键转换成配置中使用的名称，例如 `Ctrl+Q`、`ArrowLeft`、`Space`
+fn binding_name(key: &Key, modifiers: Modifiers) -> String {
+    let mut binding = String::new();
+    if modifiers.contains(Modifiers::CONTROL) {
+        binding.push_str("Ctrl+");
+    }
+    if modifiers.contains(Modifiers::ALT) {
+        binding.push_str("Alt+");
+    }
+    if modifiers.contains(Modifiers::META) {
+        binding.push_str("Meta+");
+    }
+
+    match key {
+        Key::Character(c) if c == " " => binding.push_str("Space"),
+        // 字符键已经包含了 Shift 的效果，例如 Shift + = 得到的是 +
+        Key::Character(c) => binding.push_str(&c.to_uppercase()),
+        key => {
+            if modifiers.contains(Modifiers::SHIFT) {
+                binding.push_str("Shift+");
+            }
+            binding.push_str(&key.to_string());
+        }
+    }
+
+    binding
+}
+
+fn normalize_binding(binding: &str) -> String {
+    let mut parts = binding.trim().split('+').collect::<Vec<_>>();
+    // "+" 和 "Ctrl++" 最后一段是空字符串
+    if binding.ends_with('+') {
+        parts.retain(|part| !part.is_empty());
+        parts.push("+");
+    }
+    let Some((key, modifiers)) = parts.split_last() else {
+        return String::new();
+    };
+    let key = key.trim();
+
+    let is_character = key.chars().count() == 1;
+    let mut binding = String::new();
+    for name in ["Ctrl", "Alt", "Meta", "Shift"] {
+        // 和 binding_name 一致，字符键不记录 Shift
+        if name == "Shift" && is_character {
+            continue;
+        }
+        if modifiers
+            .iter()
+            .any(|modifier| modifier.trim().eq_ignore_ascii_case(name))
+        {
+            binding.push_str(name);
+            binding.push('+');
+        }
+    }
+    if is_character {
+        binding.push_str(&key.to_uppercase());
+    } else {
+        binding.push_str(key);
+    }
+    binding
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_bindings() {
+        assert_eq!(normalize_binding("ctrl+q"), "Ctrl+Q");
+        assert_eq!(normalize_binding("+"), "+");
+        assert_eq!(normalize_binding("Ctrl++"), "Ctrl++");
+        // 字符键不记录 Shift
+        assert_eq!(normalize_binding("Shift+a"), "A");
+        assert_eq!(normalize_binding("shift+ArrowLeft"), "Shift+ArrowLeft");
+        assert_eq!(normalize_binding(" alt + ctrl + x "), "Ctrl+Alt+X");
+    }
+
+    #[test]
+    fn binding_names_match_normalized_bindings() {
+        let q = Key::Character("q".to_string());
+        assert_eq!(binding_name(&q, Modifiers::CONTROL), "Ctrl+Q");
+        let space = Key::Character(" ".to_string());
+        assert_eq!(binding_name(&space, Modifiers::empty()), "Space");
+        let plus = Key::Character("+".to_string());
+        assert_eq!(
+            binding_name(&plus, Modifiers::SHIFT),
+            normalize_binding("Shift++")
+        );
+        assert_eq!(
+            binding_name(&Key::ArrowLeft, Modifiers::SHIFT),
+            normalize_binding("shift+ArrowLeft")
+        );
+    }
+
+    #[test]
+    fn default_keymap_has_no_conflicts() {
+        let keymap = effective_keymap(&BTreeMap::new());
+        assert_eq!(find_conflicts(&keymap), []);
+        let space = Key::Character(" ".to_string());
+        assert_eq!(
+            find_action(&keymap, &space, Modifiers::empty()),
+            Some(KeyAction::StartStopTimer)
+        );
+    }
+
+    #[test]
+    fn conflicts_are_reported_per_key() {
+        let custom = BTreeMap::from([
+            (KeyAction::Quit, "l".to_string()),
+            (KeyAction::ShowHelp, "ctrl+q".to_string()),
+            (KeyAction::NextProfile, "Ctrl+Q".to_string()),
+            // 取消绑定之后不再冲突
+            (KeyAction::ToggleGhost, String::new()),
+            (KeyAction::OpenSettings, "G".to_string()),
+        ]);
+        let conflicts = find_conflicts(&effective_keymap(&custom));
+        assert_eq!(
+            conflicts,
+            [
+                (
+                    "Ctrl+Q".to_string(),
+                    vec![KeyAction::NextProfile, KeyAction::ShowHelp]
+                ),
+                (
+                    "L".to_string(),
+                    vec![KeyAction::ToggleLock, KeyAction::Quit]
+                ),
+            ]
+        );
+    }
+}
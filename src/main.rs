@@ -6,36 +6,216 @@
 mod app;
 mod app_config;
 mod app_state;
+mod audio;
+mod calendar;
 mod canvas_utils;
+mod chime;
+mod click_sound;
+mod cli;
 mod colors;
 mod components;
 mod constant;
+mod control;
+mod countdown;
+mod digit_layout;
+mod event_hooks;
 mod hooks;
+mod instance;
+mod keymap;
+mod placement;
+mod process;
+mod resize_geometry;
+mod snap;
 mod times;
 
 use app::App;
-use app_config::to_window_level;
+use app_config::{read_env_layer, to_window_level, AppConfig, ConfigFormat};
 use app_state::AppState;
-use constant::RATIO;
+use clap::Parser;
+use cli::{Cli, Command, CtlCommand};
+use constant::{MAX_SIZE, MIN_SIZE, RATIO};
 use freya::{launch::launch_cfg, prelude::LaunchConfig};
+use instance::Instance;
 
 fn main() {
-    let app_state = AppState::new();
+    env_logger::init();
+    let cli = Cli::parse();
+
+    if let Some(Command::Ctl { command }) = &cli.command {
+        run_ctl(command);
+        return;
+    }
+
+    if let Some(conf_path) = cli.config.clone() {
+        AppConfig::set_conf_path(conf_path);
+    }
+    if let Some(conf_path) = &cli.validate_config {
+        validate_config(conf_path);
+        return;
+    }
+    if let Some(format_name) = &cli.convert_config {
+        convert_config(format_name);
+        return;
+    }
+
+    // 检查和转换的都是配置文件本身，环境变量不能掩盖文件中的错误，所以之后才读取
+    match read_env_layer() {
+        Ok(env_layer) => AppConfig::set_env_layer(env_layer),
+        Err(errors) => {
+            for error in errors {
+                eprintln!("Invalid environment variable: {error}");
+            }
+            std::process::exit(2);
+        }
+    }
+
+    let overrides = cli.overrides();
+    if let Err(errors) = overrides.validate() {
+        for error in errors {
+            eprintln!("Invalid argument: {error}");
+        }
+        std::process::exit(2);
+    }
+
+    // 先获取单实例锁再读取和保存配置，以免两个实例同时写入配置文件
+    // 已经有时钟在运行时，把命令行参数交给它处理；只打印配置时不需要锁
+    if !cli.print_config {
+        acquire_instance();
+    }
+
+    // 配置文件无法读取时不启动，以免之后保存的默认配置覆盖它
+    let mut app_state = match AppState::new(overrides) {
+        Ok(app_state) => app_state,
+        Err(e) => {
+            eprintln!("Failed to load {}: {e}", AppConfig::get_conf_path());
+            std::process::exit(1);
+        }
+    };
+    if let Some(profile) = &cli.profile {
+        if !app_state.app_conf.profiles.contains_key(profile) {
+            eprintln!("Unknown profile: {profile}");
+            std::process::exit(2);
+        }
+    }
+    if cli.save {
+        if let Err(e) = app_state.persist_overrides() {
+            eprintln!("Failed to save config: {e}");
+            std::process::exit(1);
+        }
+    }
+    if cli.print_config {
+        let conf_format = AppConfig::get_conf_format();
+        match app_state.app_conf.to_string(conf_format) {
+            Ok(config_str) => print!("{config_str}"),
+            Err(e) => {
+                eprintln!("Failed to print config: {e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
 
     let window_width = app_state.app_conf.size;
     let window_level = to_window_level(app_state.app_conf.window_level);
 
-    let config = LaunchConfig::<AppState>::builder()
-        .with_physical_size((window_width, window_width / RATIO as f64))
+    let config = LaunchConfig::<AppState>::builder();
+    // 没有记录显示器的旧配置中 size 是物理像素，启动之后再换算成逻辑像素
+    let config = if app_state.app_conf.monitor.is_some() || app_state.is_size_overridden() {
+        config.with_size(window_width, window_width / RATIO as f64)
+    } else {
+        config.with_physical_size((window_width, window_width / RATIO as f64))
+    };
+    let config = config
         .with_position(app_state.app_conf.x, app_state.app_conf.y)
         .with_decorations(false)
         .with_transparency(true)
         .with_skip_taskbar(true)
         .with_window_level(window_level)
-        .with_resizable(false)
+        // 窗口保持可以调整大小，窗口管理器才能接手边缘的拖动，最小和最大尺寸由这里限制
+        .with_min_size(MIN_SIZE, MIN_SIZE / RATIO as f64)
+        .with_max_size(MAX_SIZE, MAX_SIZE / RATIO as f64)
         .with_title("Flip clock window")
         .with_background("transparent")
         .with_state(app_state);
 
     launch_cfg(App, config.build());
 }
+
+/// 获取单实例锁，参数已经转发给正在运行的时钟时直接退出
+///
+/// 无法确定有没有其他时钟在运行时也退出，以免两个时钟同时写入配置文件
+fn acquire_instance() {
+    let args = std::env::args().skip(1).collect();
+    match instance::acquire(args) {
+        Ok(Instance::Primary) => {}
+        Ok(Instance::Forwarded(response)) => {
+            if let Some(error) = response.error {
+                eprintln!("The running clock rejected the arguments: {error}");
+                std::process::exit(2);
+            }
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("Failed to acquire the single instance lock: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_ctl(command: &CtlCommand) {
+    let response = match control::send_request(&command.to_request()) {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("Failed to reach the running clock: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    println!("{}", serde_json::to_string_pretty(&response).unwrap());
+    if !response.ok {
+        std::process::exit(1);
+    }
+}
+
+fn validate_config(conf_path: &str) {
+    let conf_format = ConfigFormat::from_path(conf_path);
+    let config_str = match std::fs::read_to_string(conf_path) {
+        Ok(config_str) => config_str,
+        Err(e) => {
+            eprintln!("{conf_path}: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let app_conf = match AppConfig::try_parse(&config_str, conf_format) {
+        Ok((app_conf, _)) => app_conf,
+        Err(e) => {
+            eprintln!("{conf_path}: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(errors) = app_conf.validate() {
+        for error in errors {
+            eprintln!("{conf_path}: {error}");
+        }
+        std::process::exit(1);
+    }
+
+    println!("{conf_path}: ok");
+}
+
+fn convert_config(format_name: &str) {
+    let Some(conf_format) = ConfigFormat::from_name(format_name) else {
+        eprintln!("Unknown config format: {format_name}");
+        std::process::exit(2);
+    };
+
+    match AppConfig::convert(conf_format) {
+        Ok(conf_path) => println!("Config converted to {conf_path}"),
+        Err(e) => {
+            eprintln!("Failed to convert config: {e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use freya::prelude::{Area, Point2D, Size2D};
+use serde::{Deserialize, Serialize};
+
+// 窗口至少要有这么多像素留在显示器内，才认为它还能被拖回来
+const MIN_VISIBLE: f32 = 50.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+impl Anchor {
+    pub const ALL: [Anchor; 5] = [
+        Anchor::TopLeft,
+        Anchor::TopRight,
+        Anchor::BottomLeft,
+        Anchor::BottomRight,
+        Anchor::Center,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Anchor::TopLeft => "Top left",
+            Anchor::TopRight => "Top right",
+            Anchor::BottomLeft => "Bottom left",
+            Anchor::BottomRight => "Bottom right",
+            Anchor::Center => "Center",
+        }
+    }
+
+    pub fn next(&self) -> Anchor {
+        let index = Anchor::ALL.iter().position(|anchor| anchor == self).unwrap();
+        Anchor::ALL[(index + 1) % Anchor::ALL.len()]
+    }
+}
+
+/// 窗口所在的显示器，用来判断配置中的大小是在哪个缩放比例下记录的
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorIdentity {
+    pub name: String,
+    pub scale_factor: f32,
+}
+
+/// 相对于某个显示器的窗口位置，显示器的分辨率或排列变化之后仍然有效
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PositionPreset {
+    pub anchor: Anchor,
+    // 显示器的序号，不存在时使用第一个显示器
+    #[serde(default)]
+    pub monitor: usize,
+    // 和显示器边缘的距离，单位是逻辑像素
+    #[serde(default)]
+    pub margin: f32,
+}
+
+/// 预设位置对应的窗口左上角，显示器和窗口大小都是物理像素，`scale_factor` 用来换算边距
+pub fn preset_position(
+    preset: PositionPreset,
+    monitors: &[Area],
+    window_size: Size2D,
+    scale_factor: f32,
+) -> Point2D {
+    let Some(monitor) = monitors.get(preset.monitor).or(monitors.first()) else {
+        return Point2D::zero();
+    };
+
+    let margin = preset.margin * scale_factor;
+    let left = monitor.min_x() + margin;
+    let right = monitor.max_x() - margin - window_size.width;
+    let top = monitor.min_y() + margin;
+    let bottom = monitor.max_y() - margin - window_size.height;
+
+    match preset.anchor {
+        Anchor::TopLeft => Point2D::new(left, top),
+        Anchor::TopRight => Point2D::new(right, top),
+        Anchor::BottomLeft => Point2D::new(left, bottom),
+        Anchor::BottomRight => Point2D::new(right, bottom),
+        Anchor::Center => Point2D::new(
+            monitor.min_x() + (monitor.width() - window_size.width) / 2.0,
+            monitor.min_y() + (monitor.height() - window_size.height) / 2.0,
+        ),
+    }
+}
+
+/// 窗口所在的显示器序号，窗口不在任何显示器上时返回离它最近的显示器
+pub fn monitor_of(window: Area, monitors: &[Area]) -> Option<usize> {
+    let center = window.center();
+    monitors
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| distance(center, a).total_cmp(&distance(center, b)))
+        .map(|(index, _)| index)
+}
+
+/// 窗口跑到所有显示器之外时，把它移回最近的显示器内，否则保持原来的位置
+pub fn clamp_on_screen(window: Area, monitors: &[Area]) -> Point2D {
+    let is_visible = monitors.iter().any(|monitor| {
+        monitor.intersection(&window).is_some_and(|visible| {
+            visible.width() >= MIN_VISIBLE && visible.height() >= MIN_VISIBLE
+        })
+    });
+    if is_visible {
+        return window.origin;
+    }
+
+    let Some(monitor) = monitor_of(window, monitors).map(|index| monitors[index]) else {
+        return window.origin;
+    };
+    Point2D::new(
+        window
+            .origin
+            .x
+            .min(monitor.max_x() - window.width())
+            .max(monitor.min_x()),
+        window
+            .origin
+            .y
+            .min(monitor.max_y() - window.height())
+            .max(monitor.min_y()),
+    )
+}
+
+// 点到矩形的距离，点在矩形内时为 0
+fn distance(point: Point2D, area: &Area) -> f32 {
+    let dx = (area.min_x() - point.x).max(point.x - area.max_x()).max(0.0);
+    let dy = (area.min_y() - point.y).max(point.y - area.max_y()).max(0.0);
+    (dx * dx + dy * dy).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(x: f32, y: f32, width: f32, height: f32) -> Area {
+        Area::new(Point2D::new(x, y), Size2D::new(width, height))
+    }
+
+    fn preset(anchor: Anchor, monitor: usize, margin: f32) -> PositionPreset {
+        PositionPreset {
+            anchor,
+            monitor,
+            margin,
+        }
+    }
+
+    // 主显示器在左边，第二个显示器在右边并且高一些
+    fn monitors() -> Vec<Area> {
+        vec![area(0.0, 0.0, 1920.0, 1080.0), area(1920.0, -200.0, 2560.0, 1440.0)]
+    }
+
+    #[test]
+    fn preset_positions_follow_the_anchor() {
+        let monitors = monitors();
+        let window = Size2D::new(400.0, 200.0);
+        let position = |anchor| preset_position(preset(anchor, 1, 10.0), &monitors, window, 1.0);
+
+        assert_eq!(position(Anchor::TopLeft), Point2D::new(1930.0, -190.0));
+        assert_eq!(position(Anchor::TopRight), Point2D::new(4070.0, -190.0));
+        assert_eq!(position(Anchor::BottomLeft), Point2D::new(1930.0, 1030.0));
+        assert_eq!(position(Anchor::BottomRight), Point2D::new(4070.0, 1030.0));
+        assert_eq!(position(Anchor::Center), Point2D::new(3000.0, 420.0));
+    }
+
+    #[test]
+    fn preset_margin_is_scaled() {
+        let position = preset_position(
+            preset(Anchor::BottomRight, 0, 10.0),
+            &monitors(),
+            Size2D::new(400.0, 200.0),
+            2.0,
+        );
+        assert_eq!(position, Point2D::new(1500.0, 860.0));
+    }
+
+    #[test]
+    fn missing_monitor_falls_back_to_the_first() {
+        let window = Size2D::new(400.0, 200.0);
+        let position = preset_position(preset(Anchor::TopLeft, 5, 0.0), &monitors(), window, 1.0);
+        assert_eq!(position, Point2D::zero());
+
+        let position = preset_position(preset(Anchor::Center, 0, 0.0), &[], window, 1.0);
+        assert_eq!(position, Point2D::zero());
+    }
+
+    #[test]
+    fn monitor_of_picks_the_nearest() {
+        let monitors = monitors();
+        assert_eq!(monitor_of(area(100.0, 100.0, 400.0, 200.0), &monitors), Some(0));
+        // 跨在两个显示器之间时按中心点判断
+        assert_eq!(monitor_of(area(1800.0, 100.0, 400.0, 200.0), &monitors), Some(1));
+        // 不在任何显示器上
+        assert_eq!(monitor_of(area(-1000.0, 500.0, 400.0, 200.0), &monitors), Some(0));
+        assert_eq!(monitor_of(area(3000.0, 2000.0, 400.0, 200.0), &monitors), Some(1));
+        assert_eq!(monitor_of(area(0.0, 0.0, 400.0, 200.0), &[]), None);
+    }
+
+    #[test]
+    fn visible_windows_are_not_moved() {
+        let monitors = monitors();
+        let window = area(100.0, 100.0, 400.0, 200.0);
+        assert_eq!(clamp_on_screen(window, &monitors), window.origin);
+
+        // 刚好露出 MIN_VISIBLE
+        let window = area(-350.0, 1030.0, 400.0, 200.0);
+        assert_eq!(clamp_on_screen(window, &monitors), window.origin);
+
+        // 跨在两个显示器之间
+        let window = area(1700.0, 500.0, 400.0, 200.0);
+        assert_eq!(clamp_on_screen(window, &monitors), window.origin);
+    }
+
+    #[test]
+    fn hidden_windows_are_moved_back() {
+        let monitors = monitors();
+
+        // 只露出 MIN_VISIBLE 少一点
+        let window = area(-351.0, 100.0, 400.0, 200.0);
+        assert_eq!(clamp_on_screen(window, &monitors), Point2D::new(0.0, 100.0));
+
+        let window = area(100.0, 1031.0, 400.0, 200.0);
+        assert_eq!(clamp_on_screen(window, &monitors), Point2D::new(100.0, 880.0));
+
+        // 完全在显示器之外，移回最近的显示器
+        let window = area(5000.0, 2000.0, 400.0, 200.0);
+        assert_eq!(clamp_on_screen(window, &monitors), Point2D::new(4080.0, 1040.0));
+    }
+
+    #[test]
+    fn large_windows_are_aligned_to_the_monitor_origin() {
+        let monitors = vec![area(0.0, 0.0, 1920.0, 1080.0)];
+        let window = area(-3000.0, -2000.0, 2500.0, 1500.0);
+        assert_eq!(clamp_on_screen(window, &monitors), Point2D::zero());
+    }
+
+    #[test]
+    fn nothing_to_clamp_without_monitors() {
+        let window = area(-3000.0, -2000.0, 400.0, 200.0);
+        assert_eq!(clamp_on_screen(window, &[]), window.origin);
+    }
+}
@@ -0,0 +1,61 @@
+/// 进程号对应的进程是否还在运行
+#[cfg(unix)]
+pub fn is_alive(pid: u32) -> bool {
+    // 0 和负数表示进程组，不是单个进程
+    let Some(pid) = libc::pid_t::try_from(pid).ok().filter(|pid| *pid > 0) else {
+        return false;
+    };
+    // 信号 0 只检查进程是否存在，没有权限发送信号也说明进程存在
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// 进程号对应的进程是否还在运行
+#[cfg(windows)]
+pub fn is_alive(pid: u32) -> bool {
+    use windows_sys::Win32::{
+        Foundation::{CloseHandle, STILL_ACTIVE},
+        System::Threading::{GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION},
+    };
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle == 0 as _ {
+            return false;
+        }
+        let mut exit_code = 0;
+        let ok = GetExitCodeProcess(handle, &mut exit_code);
+        CloseHandle(handle);
+        ok != 0 && exit_code == STILL_ACTIVE as u32
+    }
+}
+
+/// 结束进程组中的所有进程，进程组由 `pid` 对应的进程创建
+#[cfg(unix)]
+pub async fn kill_group(pid: u32) {
+    let Some(pid) = libc::pid_t::try_from(pid).ok().filter(|pid| *pid > 0) else {
+        return;
+    };
+    // 负数的进程号表示整个进程组
+    if unsafe { libc::kill(-pid, libc::SIGKILL) } != 0 {
+        log::debug!(
+            "failed to kill process group {pid}: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+/// 结束进程和它启动的所有子进程
+#[cfg(windows)]
+pub async fn kill_group(pid: u32) {
+    let result = tokio::process::Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .await;
+    if let Err(e) = result {
+        log::debug!("failed to kill process tree {pid}: {e}");
+    }
+}
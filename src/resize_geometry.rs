@@ -0,0 +1,311 @@
+use freya::prelude::{Point2D, ResizeDirection, Size2D};
+
+pub const NORTH_DIRECTION: [ResizeDirection; 3] = [
+    ResizeDirection::North,
+    ResizeDirection::NorthEast,
+    ResizeDirection::NorthWest,
+];
+
+pub const SOUTH_DIRECTION: [ResizeDirection; 3] = [
+    ResizeDirection::South,
+    ResizeDirection::SouthEast,
+    ResizeDirection::SouthWest,
+];
+
+pub const WEST_DIRECTION: [ResizeDirection; 3] = [
+    ResizeDirection::West,
+    ResizeDirection::NorthWest,
+    ResizeDirection::SouthWest,
+];
+
+pub const EAST_DIRECTION: [ResizeDirection; 3] = [
+    ResizeDirection::East,
+    ResizeDirection::NorthEast,
+    ResizeDirection::SouthEast,
+];
+
+pub const CORNER_DIRECTION: [ResizeDirection; 4] = [
+    ResizeDirection::NorthEast,
+    ResizeDirection::NorthWest,
+    ResizeDirection::SouthEast,
+    ResizeDirection::SouthWest,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResizeConstraints {
+    pub aspect_ratio: Option<f32>,
+    pub min_width: f32,
+    pub max_width: f32,
+    pub min_height: f32,
+    pub max_height: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowGeometry {
+    pub size: Size2D,
+    pub position: Point2D,
+}
+
+/// 按鼠标的移动距离计算调整之后的窗口大小和位置
+///
+/// `delta` 是鼠标相对于开始调整时移动的距离。从上边或左边调整时，位置由保持不动的下边或右边
+/// 推算出来，所以大小被限制住的时候窗口也不会移动。
+pub fn resize(
+    direction: ResizeDirection,
+    delta: (f32, f32),
+    start: WindowGeometry,
+    constraints: ResizeConstraints,
+) -> WindowGeometry {
+    let (delta_x, delta_y) = delta;
+    let mut size = start.size;
+
+    //包含上
+    if NORTH_DIRECTION.contains(&direction) {
+        size.height -= delta_y;
+    }
+
+    //包含下
+    if SOUTH_DIRECTION.contains(&direction) {
+        size.height += delta_y;
+    }
+
+    //包含左
+    if WEST_DIRECTION.contains(&direction) {
+        size.width -= delta_x;
+    }
+
+    //包含右
+    if EAST_DIRECTION.contains(&direction) {
+        size.width += delta_x;
+    }
+
+    let ResizeConstraints {
+        aspect_ratio,
+        min_width,
+        max_width,
+        mut min_height,
+        mut max_height,
+    } = constraints;
+
+    if let Some(aspect_ratio) = aspect_ratio {
+        min_height = min_width / aspect_ratio;
+        max_height = max_width / aspect_ratio;
+    }
+    size.width = size.width.clamp(min_width, max_width);
+    size.height = size.height.clamp(min_height, max_height);
+
+    if let Some(aspect_ratio) = aspect_ratio {
+        if CORNER_DIRECTION.contains(&direction) || start.size.width != size.width {
+            size.height = size.width / aspect_ratio;
+        } else {
+            size.width = size.height * aspect_ratio;
+        }
+    }
+
+    let mut position = start.position;
+    if NORTH_DIRECTION.contains(&direction) {
+        position.y = start.position.y + start.size.height - size.height;
+    }
+    if WEST_DIRECTION.contains(&direction) {
+        position.x = start.position.x + start.size.width - size.width;
+    }
+
+    WindowGeometry { size, position }
+}
+
+/// 根据窗口管理器调整之后的大小，反推出鼠标移动的距离
+pub fn resize_delta(direction: ResizeDirection, start_size: Size2D, size: Size2D) -> (f32, f32) {
+    let mut delta = (0.0, 0.0);
+    if NORTH_DIRECTION.contains(&direction) {
+        delta.1 = start_size.height - size.height;
+    }
+    if SOUTH_DIRECTION.contains(&direction) {
+        delta.1 = size.height - start_size.height;
+    }
+    if WEST_DIRECTION.contains(&direction) {
+        delta.0 = start_size.width - size.width;
+    }
+    if EAST_DIRECTION.contains(&direction) {
+        delta.0 = size.width - start_size.width;
+    }
+    delta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ResizeDirection::*;
+
+    const ALL_DIRECTIONS: [ResizeDirection; 8] = [
+        East, West, South, North, NorthEast, NorthWest, SouthEast, SouthWest,
+    ];
+
+    fn geometry(width: f32, height: f32, x: f32, y: f32) -> WindowGeometry {
+        WindowGeometry {
+            size: Size2D::new(width, height),
+            position: Point2D::new(x, y),
+        }
+    }
+
+    fn check(
+        start: WindowGeometry,
+        constraints: ResizeConstraints,
+        delta: (f32, f32),
+        expected: [(ResizeDirection, WindowGeometry); 8],
+    ) {
+        for (direction, expected) in expected {
+            assert_eq!(
+                resize(direction, delta, start, constraints),
+                expected,
+                "{direction:?} by {delta:?}"
+            );
+        }
+    }
+
+    const FREE: ResizeConstraints = ResizeConstraints {
+        aspect_ratio: None,
+        min_width: 200.0,
+        max_width: 1000.0,
+        min_height: 100.0,
+        max_height: 400.0,
+    };
+
+    const RATIO: ResizeConstraints = ResizeConstraints {
+        aspect_ratio: Some(2.5),
+        min_width: 250.0,
+        max_width: 1000.0,
+        min_height: 0.0,
+        max_height: f32::MAX,
+    };
+
+    #[test]
+    fn free_resize_within_limits() {
+        check(
+            geometry(600.0, 200.0, 100.0, 100.0),
+            FREE,
+            (50.0, 20.0),
+            [
+                (East, geometry(650.0, 200.0, 100.0, 100.0)),
+                (West, geometry(550.0, 200.0, 150.0, 100.0)),
+                (South, geometry(600.0, 220.0, 100.0, 100.0)),
+                (North, geometry(600.0, 180.0, 100.0, 120.0)),
+                (NorthEast, geometry(650.0, 180.0, 100.0, 120.0)),
+                (NorthWest, geometry(550.0, 180.0, 150.0, 120.0)),
+                (SouthEast, geometry(650.0, 220.0, 100.0, 100.0)),
+                (SouthWest, geometry(550.0, 220.0, 150.0, 100.0)),
+            ],
+        );
+    }
+
+    #[test]
+    fn free_resize_clamps_and_keeps_the_opposite_edges() {
+        check(
+            geometry(600.0, 200.0, 100.0, 100.0),
+            FREE,
+            (1000.0, 1000.0),
+            [
+                (East, geometry(1000.0, 200.0, 100.0, 100.0)),
+                (West, geometry(200.0, 200.0, 500.0, 100.0)),
+                (South, geometry(600.0, 400.0, 100.0, 100.0)),
+                (North, geometry(600.0, 100.0, 100.0, 200.0)),
+                (NorthEast, geometry(1000.0, 100.0, 100.0, 200.0)),
+                (NorthWest, geometry(200.0, 100.0, 500.0, 200.0)),
+                (SouthEast, geometry(1000.0, 400.0, 100.0, 100.0)),
+                (SouthWest, geometry(200.0, 400.0, 500.0, 100.0)),
+            ],
+        );
+        check(
+            geometry(600.0, 200.0, 100.0, 100.0),
+            FREE,
+            (-1000.0, -1000.0),
+            [
+                (East, geometry(200.0, 200.0, 100.0, 100.0)),
+                (West, geometry(1000.0, 200.0, -300.0, 100.0)),
+                (South, geometry(600.0, 100.0, 100.0, 100.0)),
+                (North, geometry(600.0, 400.0, 100.0, -100.0)),
+                (NorthEast, geometry(200.0, 400.0, 100.0, -100.0)),
+                (NorthWest, geometry(1000.0, 400.0, -300.0, -100.0)),
+                (SouthEast, geometry(200.0, 100.0, 100.0, 100.0)),
+                (SouthWest, geometry(1000.0, 100.0, -300.0, 100.0)),
+            ],
+        );
+    }
+
+    #[test]
+    fn ratio_resize_within_limits() {
+        check(
+            geometry(500.0, 200.0, 100.0, 100.0),
+            RATIO,
+            (100.0, 100.0),
+            [
+                (East, geometry(600.0, 240.0, 100.0, 100.0)),
+                (West, geometry(400.0, 160.0, 200.0, 100.0)),
+                (South, geometry(750.0, 300.0, 100.0, 100.0)),
+                (North, geometry(250.0, 100.0, 100.0, 200.0)),
+                (NorthEast, geometry(600.0, 240.0, 100.0, 60.0)),
+                (NorthWest, geometry(400.0, 160.0, 200.0, 140.0)),
+                (SouthEast, geometry(600.0, 240.0, 100.0, 100.0)),
+                (SouthWest, geometry(400.0, 160.0, 200.0, 100.0)),
+            ],
+        );
+    }
+
+    #[test]
+    fn ratio_resize_clamps_and_keeps_the_opposite_edges() {
+        check(
+            geometry(500.0, 200.0, 100.0, 100.0),
+            RATIO,
+            (2000.0, 2000.0),
+            [
+                (East, geometry(1000.0, 400.0, 100.0, 100.0)),
+                (West, geometry(250.0, 100.0, 350.0, 100.0)),
+                (South, geometry(1000.0, 400.0, 100.0, 100.0)),
+                (North, geometry(250.0, 100.0, 100.0, 200.0)),
+                (NorthEast, geometry(1000.0, 400.0, 100.0, -100.0)),
+                (NorthWest, geometry(250.0, 100.0, 350.0, 200.0)),
+                (SouthEast, geometry(1000.0, 400.0, 100.0, 100.0)),
+                (SouthWest, geometry(250.0, 100.0, 350.0, 100.0)),
+            ],
+        );
+        check(
+            geometry(500.0, 200.0, 100.0, 100.0),
+            RATIO,
+            (-2000.0, -2000.0),
+            [
+                (East, geometry(250.0, 100.0, 100.0, 100.0)),
+                (West, geometry(1000.0, 400.0, -400.0, 100.0)),
+                (South, geometry(250.0, 100.0, 100.0, 100.0)),
+                (North, geometry(1000.0, 400.0, 100.0, -100.0)),
+                (NorthEast, geometry(250.0, 100.0, 100.0, 200.0)),
+                (NorthWest, geometry(1000.0, 400.0, -400.0, -100.0)),
+                (SouthEast, geometry(250.0, 100.0, 100.0, 100.0)),
+                (SouthWest, geometry(1000.0, 400.0, -400.0, 100.0)),
+            ],
+        );
+    }
+
+    #[test]
+    fn resize_delta_inverts_resize() {
+        let start = geometry(600.0, 200.0, 100.0, 100.0);
+        for direction in ALL_DIRECTIONS {
+            let size = resize(direction, (50.0, 20.0), start, FREE).size;
+            let (delta_x, delta_y) = resize_delta(direction, start.size, size);
+            let expected_x = if size.width == start.size.width {
+                0.0
+            } else {
+                50.0
+            };
+            let expected_y = if size.height == start.size.height {
+                0.0
+            } else {
+                20.0
+            };
+            assert_eq!(
+                (delta_x, delta_y),
+                (expected_x, expected_y),
+                "{direction:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use freya::prelude::{Area, Point2D, ResizeDirection};
+
+use crate::resize_geometry::{
+    ResizeConstraints, WindowGeometry, EAST_DIRECTION, NORTH_DIRECTION, SOUTH_DIRECTION,
+    WEST_DIRECTION,
+};
+
+/// 窗口可以吸附的竖线和横线，来自显示器的边缘和其他时钟窗口的边缘
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnapLines {
+    pub xs: Vec<f32>,
+    pub ys: Vec<f32>,
+}
+
+impl SnapLines {
+    pub fn from_areas<'a>(areas: impl IntoIterator<Item = &'a Area>) -> Self {
+        let mut lines = SnapLines::default();
+        for area in areas {
+            lines.xs.extend([area.min_x(), area.max_x()]);
+            lines.ys.extend([area.min_y(), area.max_y()]);
+        }
+        lines
+    }
+}
+
+/// 拖动窗口时，把离得足够近的窗口边缘吸附到最近的线上
+pub fn snap_position(window: Area, lines: &SnapLines, distance: f32) -> Point2D {
+    let x = nearest_offset(&[window.min_x(), window.max_x()], &lines.xs, distance)
+        .map(|offset| window.origin.x + offset)
+        .unwrap_or(window.origin.x);
+    let y = nearest_offset(&[window.min_y(), window.max_y()], &lines.ys, distance)
+        .map(|offset| window.origin.y + offset)
+        .unwrap_or(window.origin.y);
+    Point2D::new(x, y)
+}
+
+/// 调整窗口大小时，只吸附正在移动的边，锁定宽高比时另一条边随之变化
+///
+/// 吸附之后的大小仍然限制在 `constraints` 之内，超出时停在限制的大小，不再贴住那条线。
+pub fn snap_resize(
+    direction: ResizeDirection,
+    geometry: WindowGeometry,
+    lines: &SnapLines,
+    distance: f32,
+    constraints: ResizeConstraints,
+) -> WindowGeometry {
+    let WindowGeometry {
+        mut size,
+        mut position,
+    } = geometry;
+    let right = position.x + size.width;
+    let bottom = position.y + size.height;
+
+    let horizontal = EAST_DIRECTION.contains(&direction) || WEST_DIRECTION.contains(&direction);
+
+    if horizontal {
+        let edge = if EAST_DIRECTION.contains(&direction) {
+            right
+        } else {
+            position.x
+        };
+        let Some(offset) = nearest_offset(&[edge], &lines.xs, distance) else {
+            return geometry;
+        };
+        if EAST_DIRECTION.contains(&direction) {
+            size.width += offset;
+        } else {
+            size.width -= offset;
+        }
+    } else {
+        let edge = if SOUTH_DIRECTION.contains(&direction) {
+            bottom
+        } else {
+            position.y
+        };
+        let Some(offset) = nearest_offset(&[edge], &lines.ys, distance) else {
+            return geometry;
+        };
+        if SOUTH_DIRECTION.contains(&direction) {
+            size.height += offset;
+        } else {
+            size.height -= offset;
+        }
+    }
+
+    let ResizeConstraints {
+        aspect_ratio,
+        min_width,
+        max_width,
+        mut min_height,
+        mut max_height,
+    } = constraints;
+    if let Some(aspect_ratio) = aspect_ratio {
+        min_height = min_width / aspect_ratio;
+        max_height = max_width / aspect_ratio;
+    }
+    size.width = size.width.clamp(min_width, max_width);
+    size.height = size.height.clamp(min_height, max_height);
+    if let Some(aspect_ratio) = aspect_ratio {
+        if horizontal {
+            size.height = size.width / aspect_ratio;
+        } else {
+            size.width = size.height * aspect_ratio;
+        }
+    }
+
+    // 和调整大小时一样，上边和左边移动时下边和右边保持不动
+    if WEST_DIRECTION.contains(&direction) {
+        position.x = right - size.width;
+    }
+    if NORTH_DIRECTION.contains(&direction) {
+        position.y = bottom - size.height;
+    }
+
+    WindowGeometry { size, position }
+}
+
+// 从多条边里找到离某条线最近的一条，返回需要移动的距离
+fn nearest_offset(edges: &[f32], lines: &[f32], distance: f32) -> Option<f32> {
+    edges
+        .iter()
+        .flat_map(|edge| lines.iter().map(move |line| line - edge))
+        .filter(|offset| offset.abs() <= distance)
+        .min_by(|a, b| a.abs().total_cmp(&b.abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use freya::prelude::Size2D;
+
+    use super::*;
+
+    const CONSTRAINTS: ResizeConstraints = ResizeConstraints {
+        aspect_ratio: Some(2.0),
+        min_width: 200.0,
+        max_width: 1000.0,
+        min_height: 0.0,
+        max_height: f32::MAX,
+    };
+
+    fn geometry(width: f32, height: f32, x: f32, y: f32) -> WindowGeometry {
+        WindowGeometry {
+            size: Size2D::new(width, height),
+            position: Point2D::new(x, y),
+        }
+    }
+
+    fn lines(xs: &[f32], ys: &[f32]) -> SnapLines {
+        SnapLines {
+            xs: xs.to_vec(),
+            ys: ys.to_vec(),
+        }
+    }
+
+    #[test]
+    fn snaps_the_moving_edge() {
+        let snapped = snap_resize(
+            ResizeDirection::East,
+            geometry(590.0, 295.0, 100.0, 100.0),
+            &lines(&[700.0], &[]),
+            16.0,
+            CONSTRAINTS,
+        );
+        assert_eq!(snapped, geometry(600.0, 300.0, 100.0, 100.0));
+
+        let snapped = snap_resize(
+            ResizeDirection::NorthWest,
+            geometry(590.0, 295.0, 110.0, 105.0),
+            &lines(&[100.0], &[]),
+            16.0,
+            CONSTRAINTS,
+        );
+        assert_eq!(snapped, geometry(600.0, 300.0, 100.0, 100.0));
+    }
+
+    #[test]
+    fn snapping_stays_within_the_size_limits() {
+        let snapped = snap_resize(
+            ResizeDirection::East,
+            geometry(995.0, 497.5, 0.0, 0.0),
+            &lines(&[1010.0], &[]),
+            16.0,
+            CONSTRAINTS,
+        );
+        assert_eq!(snapped, geometry(1000.0, 500.0, 0.0, 0.0));
+
+        let snapped = snap_resize(
+            ResizeDirection::North,
+            geometry(210.0, 105.0, 0.0, 100.0),
+            &lines(&[], &[110.0]),
+            16.0,
+            CONSTRAINTS,
+        );
+        assert_eq!(snapped, geometry(200.0, 100.0, 0.0, 105.0));
+    }
+}
@@ -3,7 +3,16 @@ use freya::prelude::*;
 use std::time::Duration;
 use tokio::time::sleep;
 
-pub fn use_current_time() -> (Signal<u32>, Signal<u32>, Signal<u32>) {
+/// 整个界面共用的当前时间，每秒检查一次，只在值变化时更新
+#[derive(Clone, Copy, PartialEq)]
+pub struct UseCurrentTime {
+    pub hour: Signal<u32>,
+    pub minute: Signal<u32>,
+    pub second: Signal<u32>,
+}
+
+/// 启动共用的时钟，数字和报时订阅同一个时钟，需要在 `MyApp` 中调用
+pub fn use_current_time_provider() -> UseCurrentTime {
     let mut hour = use_signal(|| Local::now().hour());
     let mut minute = use_signal(|| Local::now().minute());
     let mut second = use_signal(|| Local::now().second());
@@ -13,12 +22,31 @@ pub fn use_current_time() -> (Signal<u32>, Signal<u32>, Signal<u32>) {
             loop {
                 sleep(Duration::from_millis(1000)).await;
                 let now = Local::now();
-                hour.set(now.hour());
-                minute.set(now.minute());
-                second.set(now.second());
+                for (signal, value) in [
+                    (&mut hour, now.hour()),
+                    (&mut minute, now.minute()),
+                    (&mut second, now.second()),
+                ] {
+                    if *signal.peek() != value {
+                        signal.set(value);
+                    }
+                }
             }
         });
     });
 
+    use_context_provider(|| UseCurrentTime {
+        hour,
+        minute,
+        second,
+    })
+}
+
+pub fn use_current_time() -> (Signal<u32>, Signal<u32>, Signal<u32>) {
+    let UseCurrentTime {
+        hour,
+        minute,
+        second,
+    } = consume_context::<UseCurrentTime>();
     (hour, minute, second)
 }